@@ -4,20 +4,53 @@
 //! [`LabelHandler`], and input handling, as well as the state data that each of them need.
 
 use std::{
-    collections::hash_map::DefaultHasher, error::Error, fs::File, hash::Hasher, io::Read, process,
+    cmp,
+    collections::hash_map::DefaultHasher,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs::{File, OpenOptions},
+    hash::Hasher,
+    io::{self, Seek, SeekFrom, Write},
+    mem,
+    ops::Range,
+    path::{Path, PathBuf},
+    process,
 };
 
-use arboard::Clipboard;
 use crossterm::event::{self, Event};
 
-use crate::decoder::Encoding;
+use crate::decoder::{detect_bom, Encoding, Endian};
 use crate::{
+    bookmarks::Bookmarks,
+    buffer::{Buffer, Contents, MAP_THRESHOLD},
+    clipboard::Clipboard,
+    diff::{count_differences, differs_at},
+    dirty::DirtyBytes,
+    history::{EditHistory, Replay},
     input,
     label::LabelHandler,
+    save::save_atomic,
     screen::ScreenHandler,
+    search::{self, Query},
+    tabs::{tab_name, Tabs},
+    theme::parse_theme,
+    watch::{FileWatch, POLL_INTERVAL},
     windows::{
-        editor::Editor, jump_to_byte::JumpToByte, unsaved_changes::UnsavedChanges, KeyHandler,
-        Window,
+        adjust_offset,
+        bookmarks::{BookmarkLabel, BookmarkList},
+        checksums::Checksums,
+        editor::Editor,
+        entropy::Entropy,
+        export::Export,
+        fill::Fill,
+        jump_to_byte::JumpToByte,
+        open_file::OpenFile,
+        reload::Reload,
+        save_as::SaveAs,
+        search::Search,
+        strings::Strings,
+        unsaved_changes::UnsavedChanges,
+        KeyHandler, Window,
     },
 };
 
@@ -40,34 +73,97 @@ impl Nibble {
     }
 }
 
-/// An instance of a user action, used to implement the undo feature.
+/// The bytes between where a selection was started and the cursor, both of which are included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Selection {
+    pub(crate) anchor: usize,
+    pub(crate) cursor: usize,
+}
+
+impl Selection {
+    /// The selected offsets, regardless of whether the cursor is before or after the anchor.
+    pub(crate) fn range(&self) -> Range<usize> {
+        cmp::min(self.anchor, self.cursor)..cmp::max(self.anchor, self.cursor) + 1
+    }
+}
+
+/// An instance of a user action, used to implement the undo and redo feature.
 ///
 /// These actions record the previous state - deleting the first byte (x00) correlates to
 /// Delete(0, x00).
 pub(crate) enum Action {
-    /// Tracks a user keypress to modify the contents of the file.
-    CharacterInput(usize, u8, Option<Nibble>),
+    /// Tracks a user keypress to modify the contents of the file, along with the byte before and
+    /// after it.
+    CharacterInput(usize, u8, u8, Option<Nibble>),
 
     /// Tracks when a user deletes the byte before the cursor.
     Backspace(usize, u8),
 
     /// Tracks when a user deletes the byte at the current cursor.
     Delete(usize, u8),
+
+    /// Tracks when a user inserts a byte in insert mode.
+    Insert(usize, u8),
+}
+
+/// The reasons the contents can't be edited.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum EditError {
+    /// The file was opened or switched to be read-only.
+    ReadOnly,
+
+    /// The file was opened with `--read-only`, so it can't be switched to be editable.
+    OpenedReadOnly,
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::ReadOnly => write!(f, "File is read-only!"),
+            EditError::OpenedReadOnly => write!(f, "File was opened read-only!"),
+        }
+    }
+}
+
+impl Error for EditError {}
+
+impl From<EditError> for io::Error {
+    fn from(error: EditError) -> Self {
+        match error {
+            EditError::ReadOnly | EditError::OpenedReadOnly => {
+                io::Error::new(io::ErrorKind::PermissionDenied, error)
+            }
+        }
+    }
 }
 
 /// State Information needed by the [`ScreenHandler`] and [`KeyHandler`].
+#[allow(clippy::struct_excessive_bools)]
 pub(crate) struct AppData {
     /// The file under editing.
     pub(crate) file: File,
 
+    /// The path the file was opened from, if known, which saving replaces atomically.
+    pub(crate) path: Option<PathBuf>,
+
+    /// The name of the file, which its tab is labelled with.
+    pub(crate) name: String,
+
     /// The file content.
-    pub(crate) contents: Vec<u8>,
+    pub(crate) contents: Contents,
 
     /// The decoding used for the editor.
     pub(crate) encoding: Encoding,
 
-    /// The hashed content, used for checking if anything has been changed.
-    pub(crate) hashed_contents: u64,
+    /// The byte order the labels interpret multibyte values in.
+    pub(crate) endian: Endian,
+
+    /// The hashed content, used for checking if anything has been changed. Mapped contents aren't
+    /// hashed, see [`AppData::has_unsaved_changes`].
+    pub(crate) hashed_contents: Option<u64>,
+
+    /// The length of the file when it was opened or last saved.
+    pub(crate) saved_len: usize,
 
     /// Offset of the first content byte that is visible on the screen.
     pub(crate) start_address: usize,
@@ -78,6 +174,17 @@ pub(crate) struct AppData {
     /// The nibble that is currently selected in the Hex viewport.
     pub(crate) nibble: Nibble,
 
+    /// Whether typing inserts new bytes instead of overwriting the ones under the cursor.
+    pub(crate) insert_mode: bool,
+
+    /// Whether all edits and saving are refused, which is checked by [`AppData::edit`] and
+    /// [`AppData::save`].
+    pub(crate) read_only: bool,
+
+    /// Whether the file was opened only for reading, which keeps it read-only for good, see
+    /// [`AppData::toggle_read_only`].
+    pub(crate) opened_read_only: bool,
+
     /// The last clicked (key down AND key up) label/window.
     pub(crate) last_click: Window,
 
@@ -90,19 +197,413 @@ pub(crate) struct AppData {
     /// The nibble that was last hovered from the drag.
     pub(crate) drag_nibble: Option<Nibble>,
 
-    /// Copies label data to your clipboard.
+    /// Copies label data and selections to your clipboard.
     pub(crate) clipboard: Option<Clipboard>,
 
     /// The editor that is currently selected. This editor will be refocused upon a popup closing.
     pub(crate) editor: Editor,
 
     /// A series of actions that keep track of what the user does.
-    pub(crate) actions: Vec<Action>,
+    pub(crate) history: EditHistory,
+
+    /// The most recent search, which can be repeated to find further matches.
+    pub(crate) search: Option<Query>,
+
+    /// The bytes of the most recently found match, highlighted in both editors.
+    pub(crate) search_match: Option<Range<usize>>,
+
+    /// The offsets the user has bookmarked to jump back to.
+    pub(crate) bookmarks: Bookmarks,
+
+    /// The contents of the file that is compared against, whose differing bytes are highlighted.
+    pub(crate) diff: Option<Vec<u8>>,
+
+    /// The number of bytes that differ from `diff`, which every edit keeps up to date.
+    pub(crate) differing: usize,
+
+    /// The bytes that were modified since the file was opened or last saved, highlighted in both
+    /// editors.
+    pub(crate) dirty: DirtyBytes,
+
+    /// The file on disk, which is checked for changes by other processes to offer reloading it.
+    /// Mapped files aren't watched, as their contents would change underneath heh.
+    pub(crate) watch: Option<FileWatch>,
+}
+
+impl AppData {
+    /// Creates the state for editing `contents`, which were read from `file`.
+    pub(crate) fn new(file: File, contents: Contents, encoding: Encoding) -> Self {
+        Self {
+            file,
+            path: None,
+            name: String::new(),
+            contents,
+            encoding,
+            endian: Endian::default(),
+            hashed_contents: None,
+            saved_len: 0,
+            start_address: 0,
+            offset: 0,
+            nibble: Nibble::Beginning,
+            insert_mode: false,
+            read_only: false,
+            opened_read_only: false,
+            last_click: Window::Unhandled,
+            drag_enabled: false,
+            last_drag: None,
+            drag_nibble: None,
+            clipboard: None,
+            editor: Editor::Hex,
+            history: EditHistory::default(),
+            search: None,
+            search_match: None,
+            bookmarks: Bookmarks::default(),
+            diff: None,
+            differing: 0,
+            dirty: DirtyBytes::default(),
+            watch: None,
+        }
+    }
+
+    /// Reads `file` for editing, or maps it into memory if `map` is set.
+    ///
+    /// If no encoding is given, it is picked based on the byte order mark at the start of the file,
+    /// falling back to ASCII if there is none. The encoding of the byte order mark is returned as
+    /// well, if there is one.
+    pub(crate) fn open(
+        mut file: File,
+        encoding: Option<Encoding>,
+        map: bool,
+    ) -> io::Result<(Self, Option<Encoding>)> {
+        let contents = Contents::open(&mut file, map)?;
+        let bom = detect_bom(&contents).map(|(detected, _)| detected);
+        let encoding = encoding.or(bom).unwrap_or(Encoding::Ascii);
+        let mut data = Self::new(file, contents, encoding);
+        data.mark_saved();
+        Ok((data, bom))
+    }
+
+    /// Hashes the contents of a file and is used to check if there are any changes.
+    pub(crate) fn hash_contents(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&self.contents);
+        hasher.finish()
+    }
+
+    /// Whether the contents changed since the file was opened or last saved.
+    pub(crate) fn has_unsaved_changes(&self) -> bool {
+        match self.hashed_contents {
+            Some(hash) => self.hash_contents() != hash,
+            // Mapped contents aren't hashed. Their bytes can only be overwritten, as inserting or
+            // removing one reads them into memory, so the modified bytes and the length account
+            // for every change since.
+            None => !self.dirty.is_empty() || self.contents.len() != self.saved_len,
+        }
+    }
+
+    /// Fails if the file is read-only, in which case neither the contents nor the file may change.
+    pub(crate) fn check_writable(&self) -> Result<(), EditError> {
+        if self.read_only {
+            Err(EditError::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Switches between refusing and allowing edits and saving.
+    ///
+    /// A file that was opened read-only stays so, as saving would replace it on disk even though it
+    /// was only opened for reading.
+    pub(crate) fn toggle_read_only(&mut self) -> Result<(), EditError> {
+        if self.opened_read_only {
+            return Err(EditError::OpenedReadOnly);
+        }
+        self.read_only = !self.read_only;
+        Ok(())
+    }
+
+    /// Applies `action` to the contents and records it to be undone.
+    ///
+    /// Every edit goes through here (or [`edit_group`](Self::edit_group), [`undo`](Self::undo), and
+    /// [`redo`](Self::redo)), so that none are made while the file is read-only.
+    pub(crate) fn edit(&mut self, action: Action) -> Result<(), EditError> {
+        self.check_writable()?;
+        self.apply(&action);
+        if shifts_bytes(&action) {
+            self.count_differing();
+        }
+        self.history.push(action);
+        Ok(())
+    }
+
+    /// Like [`edit`](Self::edit), but applies all of `actions` and records them to be undone at
+    /// once.
+    pub(crate) fn edit_group(&mut self, actions: Vec<Action>) -> Result<(), EditError> {
+        self.check_writable()?;
+        for action in &actions {
+            self.apply(action);
+        }
+        if actions.iter().any(shifts_bytes) {
+            self.count_differing();
+        }
+        self.history.push_group(actions);
+        Ok(())
+    }
+
+    fn apply(&mut self, action: &Action) {
+        self.dirty.edit(action);
+        match *action {
+            Action::CharacterInput(offset, _, new, _) => self.set_byte(offset, new),
+            Action::Backspace(offset, _) | Action::Delete(offset, _) => {
+                self.remove_byte(offset);
+            }
+            Action::Insert(offset, byte) => self.insert_byte(offset, byte),
+        }
+    }
+
+    /// Reverts the most recent group of edits, returning the offset (and nibble) the cursor should
+    /// move to if there was anything to undo.
+    pub(crate) fn undo(&mut self) -> Result<Option<(usize, Option<Nibble>)>, EditError> {
+        self.check_writable()?;
+        let mut shifted = false;
+        for action in self.history.next_undo().into_iter().flatten().rev() {
+            self.dirty.undo(action);
+            shifted |= shifts_bytes(action);
+        }
+        let mut history = mem::take(&mut self.history);
+        let cursor = history.undo(self);
+        self.history = history;
+        if shifted {
+            self.count_differing();
+        }
+        Ok(cursor)
+    }
+
+    /// Reapplies the most recently undone group of edits, returning the offset (and nibble) the
+    /// cursor should move to if there was anything to redo.
+    pub(crate) fn redo(&mut self) -> Result<Option<(usize, Option<Nibble>)>, EditError> {
+        self.check_writable()?;
+        let mut shifted = false;
+        for action in self.history.next_redo().into_iter().flatten() {
+            self.dirty.edit(action);
+            shifted |= shifts_bytes(action);
+        }
+        let mut history = mem::take(&mut self.history);
+        let cursor = history.redo(self);
+        self.history = history;
+        if shifted {
+            self.count_differing();
+        }
+        Ok(cursor)
+    }
+
+    /// Whether the byte at `offset` was modified since the file was opened or last saved.
+    pub(crate) fn is_dirty(&self, offset: usize) -> bool {
+        self.dirty.is_dirty(offset)
+    }
+
+    /// Records that the file on disk now matches the contents, so that there are no unsaved
+    /// changes or modified bytes anymore.
+    pub(crate) fn mark_saved(&mut self) {
+        // Hashing mapped contents would read all of them, which mapping them avoids.
+        self.hashed_contents = match self.contents {
+            Contents::Memory(_) => Some(self.hash_contents()),
+            Contents::Mapped(_) => None,
+        };
+        self.saved_len = self.contents.len();
+        self.dirty.clear();
+        if let Some(watch) = &mut self.watch {
+            watch.refresh();
+        }
+    }
+
+    /// Sets the path the file was opened from, labelling its tab with the name of the file and
+    /// watching the file for changes.
+    pub(crate) fn set_path(&mut self, path: &Path) {
+        self.name = tab_name(path);
+        self.path = Some(path.to_owned());
+        self.watch = match self.contents {
+            Contents::Memory(_) => FileWatch::new(path),
+            Contents::Mapped(_) => None,
+        };
+    }
+
+    /// Writes the contents to the file, replacing what was in it.
+    ///
+    /// If the path of the file is known, the file is replaced atomically, see [`save_atomic`].
+    /// Otherwise, it is overwritten in place. Nothing is written while the file is read-only.
+    pub(crate) fn save(&mut self) -> io::Result<()> {
+        self.check_writable()?;
+        if let Some(path) = self.path.clone() {
+            return self.save_as(&path);
+        }
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&self.contents)?;
+        self.file.set_len(self.contents.len() as u64)?;
+        self.mark_saved();
+        Ok(())
+    }
+
+    /// Reads the file again after it was changed on disk, discarding all edits and their history.
+    ///
+    /// The cursor stays at the same offset, unless the file shrank past it. The bookmarks past the
+    /// end of a file that shrank are removed.
+    pub(crate) fn reload(&mut self) -> io::Result<()> {
+        let path = self.path.clone().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "the path of the file is unknown")
+        })?;
+        // Another program may have replaced the file rather than written to it.
+        let mut file = OpenOptions::new().read(true).write(!self.read_only).open(&path)?;
+        let contents = Contents::open(&mut file, false)?;
+        if contents.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "the file is empty now"));
+        }
+        self.file = file;
+        self.contents = contents;
+        self.history = EditHistory::default();
+        self.offset = self.offset.min(self.contents.len() - 1);
+        // The rows on the screen may be past the end now, which scrolls back to the cursor.
+        if self.start_address > self.offset {
+            self.start_address = 0;
+        }
+        self.last_drag = None;
+        self.drag_nibble = None;
+        self.search_match = None;
+        self.bookmarks.truncate(self.contents.len());
+        self.count_differing();
+        self.mark_saved();
+        Ok(())
+    }
+
+    /// Writes the contents to the file at `path`, which is edited from then on. Nothing is written
+    /// while the file is read-only.
+    pub(crate) fn save_as(&mut self, path: &Path) -> io::Result<()> {
+        self.check_writable()?;
+        save_atomic(path, &self.contents)?;
+        // The saved file is a new one, so the old one is no longer of use.
+        self.file = OpenOptions::new().read(true).write(true).open(path)?;
+        self.set_path(path);
+        self.mark_saved();
+        Ok(())
+    }
+
+    /// Overwrites the byte at `offset` with `byte`.
+    fn set_byte(&mut self, offset: usize, byte: u8) {
+        if let Some(other) = &self.diff {
+            let differed = differs_at(&self.contents, other, offset);
+            let differs = other.get(offset) != Some(byte);
+            self.differing = self.differing + usize::from(differs) - usize::from(differed);
+        }
+        self.contents[offset] = byte;
+    }
+
+    /// Compares the contents to `other` offset by offset from now on.
+    pub(crate) fn compare_with(&mut self, other: Vec<u8>) {
+        self.diff = Some(other);
+        self.count_differing();
+    }
+
+    /// Counts the bytes that differ from `diff` again, as inserting or removing a byte shifts all
+    /// the bytes after it. This is done once per group of edits rather than for every byte of it.
+    fn count_differing(&mut self) {
+        if let Some(other) = &self.diff {
+            self.differing = count_differences(&self.contents, other);
+        }
+    }
+
+    /// Inserts `byte` at `offset`, moving the cursor, selections, and bookmarks after it along with
+    /// their bytes.
+    fn insert_byte(&mut self, offset: usize, byte: u8) {
+        self.contents.insert(offset, byte);
+        let shift = |position: usize| if position >= offset { position + 1 } else { position };
+        self.shift_positions(shift);
+        self.bookmarks.shift_insert(offset);
+    }
+
+    /// Removes the byte at `offset`, moving the cursor, selections, and bookmarks after it along with
+    /// their bytes. A bookmark on the removed byte is removed as well.
+    fn remove_byte(&mut self, offset: usize) -> u8 {
+        let byte = self.contents.remove(offset);
+        let shift = |position: usize| if position > offset { position - 1 } else { position };
+        self.shift_positions(shift);
+        self.offset = self.offset.min(self.contents.len().saturating_sub(1));
+        self.bookmarks.shift_remove(offset);
+        byte
+    }
+
+    fn shift_positions(&mut self, shift: impl Fn(usize) -> usize) {
+        self.offset = shift(self.offset);
+        self.last_drag = self.last_drag.map(&shift);
+        self.search_match =
+            self.search_match.take().map(|found| shift(found.start)..shift(found.end));
+    }
+
+    /// The bytes selected by dragging or shift and the arrow keys, or just the one under the cursor
+    /// if nothing is selected.
+    pub(crate) fn selection(&self) -> Selection {
+        Selection { anchor: self.last_drag.unwrap_or(self.offset), cursor: self.offset }
+    }
+
+    /// Finds the first occurrence of `needle` at or after `start`, wrapping around to the beginning
+    /// of the contents.
+    pub(crate) fn find_bytes(&self, needle: &[u8], start: usize) -> Option<usize> {
+        search::find(&self.contents, &search::exact(needle), start)
+    }
+
+    /// Finds the last occurrence of `needle` before `start`, wrapping around to the end of the
+    /// contents.
+    pub(crate) fn find_bytes_prev(&self, needle: &[u8], start: usize) -> Option<usize> {
+        search::rfind(&self.contents, &search::exact(needle), start)
+    }
+
+    /// Finds the first occurrence of `query` at or after `start`, encoded with the active encoding.
+    /// ASCII letters are matched regardless of their case.
+    ///
+    /// Returns `None` as well if the query cannot be represented in the active encoding.
+    pub(crate) fn find_text(&self, query: &str, start: usize) -> Option<usize> {
+        let pattern = search::encode_text(query, self.encoding)?;
+        search::find(&self.contents, &pattern, start)
+    }
+
+    /// Like [`find_text`](Self::find_text), but finds the last occurrence before `start`.
+    pub(crate) fn find_text_prev(&self, query: &str, start: usize) -> Option<usize> {
+        let pattern = search::encode_text(query, self.encoding)?;
+        search::rfind(&self.contents, &pattern, start)
+    }
+
+    /// Finds the next character after the cursor that the active encoding decodes as a control
+    /// character or can't decode, wrapping around to the beginning of the contents.
+    pub(crate) fn next_nonprintable(&self) -> Option<usize> {
+        search::find_nonprintable(&self.contents, self.encoding, self.offset + 1)
+    }
+
+    /// Like [`next_nonprintable`](Self::next_nonprintable), but finds the previous one before the
+    /// cursor.
+    pub(crate) fn prev_nonprintable(&self) -> Option<usize> {
+        search::rfind_nonprintable(&self.contents, self.encoding, self.offset)
+    }
+}
+
+/// Whether `action` shifts the bytes after it, rather than overwriting one in place.
+fn shifts_bytes(action: &Action) -> bool {
+    !matches!(action, Action::CharacterInput(..))
+}
+
+impl Replay for AppData {
+    fn set(&mut self, offset: usize, byte: u8) {
+        self.set_byte(offset, byte);
+    }
+    fn insert(&mut self, offset: usize, byte: u8) {
+        self.insert_byte(offset, byte);
+    }
+    fn remove(&mut self, offset: usize) {
+        self.remove_byte(offset);
+    }
 }
 
 /// Application provides the user interaction interface and renders the terminal screen in response
 /// to user actions.
-pub(crate) struct Application {
+pub struct Application {
     /// The application's state and data.
     pub(crate) data: AppData,
 
@@ -113,6 +614,9 @@ pub(crate) struct Application {
     /// based on the current offset.
     pub(crate) labels: LabelHandler,
 
+    /// The files that are open besides the one in `data`, which is the one in the active tab.
+    pub(crate) tabs: Tabs<AppData>,
+
     /// The window that handles keyboard input. This is usually in the form of the Hex/ASCII editor
     /// or popups.
     pub(crate) key_handler: Box<dyn KeyHandler>,
@@ -122,51 +626,103 @@ impl Application {
     /// Creates a new application, focusing the Hex editor and starting with an offset of 0 by
     /// default. This is called once at the beginning of the program.
     ///
-    /// This errors out if the file specified is empty.
-    pub(crate) fn new(mut file: File, encoding: Encoding) -> Result<Self, Box<dyn Error>> {
-        let mut contents = Vec::new();
-        file.read_to_end(&mut contents).expect("Reading the contents of the file was interrupted.");
-        if contents.is_empty() {
+    /// If no encoding is given, it is picked based on the byte order mark at the start of the file,
+    /// falling back to ASCII if there is none.
+    ///
+    /// Files larger than 64 MiB are mapped into memory rather than read whole, so that opening them
+    /// is quick. Inserting or removing bytes reads them in anyway.
+    ///
+    /// # Errors
+    ///
+    /// This errors out if the file can't be read or mapped, or the terminal can't be queried for
+    /// its size. heh exits if the file specified is empty.
+    pub fn new(file: File, encoding: Option<Encoding>) -> Result<Self, Box<dyn Error>> {
+        let map = file.metadata()?.len() > MAP_THRESHOLD;
+        Self::open(file, encoding, map)
+    }
+
+    /// Like [`new`](Self::new), but refuses all edits and saving for good, and maps the file into
+    /// memory whatever its size. This way, `file` only needs to be opened for reading.
+    ///
+    /// # Errors
+    ///
+    /// This errors out for the same reasons as [`new`](Self::new).
+    pub fn new_read_only(file: File, encoding: Option<Encoding>) -> Result<Self, Box<dyn Error>> {
+        let mut app = Self::open(file, encoding, true)?;
+        app.set_read_only(true);
+        app.data.opened_read_only = true;
+        Ok(app)
+    }
+
+    fn open(file: File, encoding: Option<Encoding>, map: bool) -> Result<Self, Box<dyn Error>> {
+        let (mut data, bom) = AppData::open(file, encoding, map)?;
+        if data.contents.is_empty() {
             eprintln!("heh does not support editing empty files");
             process::exit(1);
         }
 
-        let mut labels = LabelHandler::new(&contents);
-        let clipboard = Clipboard::new().ok();
-        if clipboard.is_none() {
+        let mut labels = LabelHandler::new(&data.contents);
+        data.clipboard = Clipboard::new();
+        if data.clipboard.is_none() {
             labels.notification = String::from("Can't find clipboard!");
         }
+        if let Some(detected) = bom {
+            labels.notification = format!("{detected} BOM detected");
+        }
 
-        let mut app = Self {
-            data: AppData {
-                file,
-                contents,
-                encoding,
-                hashed_contents: 0,
-                start_address: 0,
-                offset: 0,
-                nibble: Nibble::Beginning,
-                last_click: Window::Unhandled,
-                drag_enabled: false,
-                last_drag: None,
-                drag_nibble: None,
-                clipboard,
-                editor: Editor::Hex,
-                actions: vec![],
-            },
+        Ok(Self {
+            data,
+            tabs: Tabs::default(),
             display: ScreenHandler::new()?,
             labels,
             key_handler: Box::from(Editor::Hex),
-        };
+        })
+    }
+
+    /// Sets the path the file was opened from. This allows saving to replace the file atomically
+    /// instead of overwriting it in place, and labels its tab once more files are opened.
+    pub fn set_path(&mut self, path: impl AsRef<Path>) {
+        self.data.set_path(path.as_ref());
+    }
 
-        app.data.hashed_contents = app.hash_contents();
+    /// Compares the file under editing to `contents` offset by offset, highlighting the bytes that
+    /// differ and counting them in the status bar.
+    pub fn compare_with(&mut self, contents: Vec<u8>) {
+        self.data.compare_with(contents);
+    }
 
-        Ok(app)
+    /// Shows the file in the colors of the theme file `theme`. The colors it doesn't set keep their
+    /// defaults.
+    ///
+    /// # Errors
+    ///
+    /// This errors out if a line of `theme` isn't a `key = "color"` pair of a known key and a
+    /// valid color, a comment, or empty.
+    pub fn set_theme(&mut self, theme: &str) -> Result<(), Box<dyn Error>> {
+        self.display.theme = parse_theme(theme)?;
+        Ok(())
+    }
+
+    /// Refuses all edits and saving if `read_only` is set, which can be toggled with `CNTRLr`. A
+    /// file opened with [`new_read_only`](Self::new_read_only) stays read-only either way.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.data.read_only = read_only || self.data.opened_read_only;
+    }
+
+    /// Shows `bytes_per_row` bytes in each row of the editors, as far as the terminal is wide enough
+    /// for them. If it is `None`, as many bytes as fit are shown, which is the default.
+    pub fn set_bytes_per_row(&mut self, bytes_per_row: Option<usize>) {
+        self.display.set_bytes_per_row(&mut self.data, self.key_handler.as_ref(), bytes_per_row);
+        adjust_offset(&mut self.data, &mut self.display, &mut self.labels);
     }
 
     /// A loop that repeatedly renders the terminal and modifies state based on input. Is stopped
     /// when input handling receives CNTRLq, the command to stop.
-    pub(crate) fn run(&mut self) -> Result<(), Box<dyn Error>> {
+    ///
+    /// # Errors
+    ///
+    /// This errors out if the terminal can't be set up, drawn to, or read input from.
+    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
         ScreenHandler::setup()?;
         loop {
             self.render_display()?;
@@ -181,7 +737,7 @@ impl Application {
     /// Renders the display. This is a wrapper around [`ScreenHandler`'s
     /// render](ScreenHandler::render) method.
     fn render_display(&mut self) -> Result<(), Box<dyn Error>> {
-        self.display.render(&mut self.data, &self.labels, self.key_handler.as_ref())?;
+        self.display.render(&mut self.data, &self.labels, self.key_handler.as_ref(), &self.tabs)?;
         Ok(())
     }
 
@@ -189,10 +745,15 @@ impl Application {
     /// [Application's `key_handler` method](Application::key_handler) to determine what to do for
     /// key input.
     fn handle_input(&mut self) -> Result<bool, Box<dyn Error>> {
+        // A watched file is checked for changes whenever no input arrives for a while.
+        if self.data.watch.is_some() && !event::poll(POLL_INTERVAL)? {
+            self.check_file_changed();
+            return Ok(true);
+        }
         let event = event::read()?;
         match event {
             Event::Key(key) => {
-                return input::handle_key_input(self, key);
+                return Ok(input::handle_key_input(self, key));
             }
             Event::Mouse(mouse) => {
                 input::handle_mouse_input(self, mouse);
@@ -202,11 +763,90 @@ impl Application {
         Ok(true)
     }
 
-    /// Hashes the contents of a file and is used to check if there are any changes.
-    pub(crate) fn hash_contents(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        hasher.write(&self.data.contents);
-        hasher.finish()
+    /// Offers to reload the file in the active tab if it was changed on disk. This waits until no
+    /// popup is open, so that nothing the user is typing gets interrupted.
+    fn check_file_changed(&mut self) {
+        let editing = self.key_handler.is_focusing(Window::Hex)
+            || self.key_handler.is_focusing(Window::Ascii);
+        if editing && self.data.watch.as_mut().map_or(false, FileWatch::changed) {
+            self.set_focused_window(Window::Reload);
+        }
+    }
+
+    /// Reloads the file in the active tab after it was changed on disk.
+    pub(crate) fn reload(&mut self) {
+        if let Err(error) = self.data.reload() {
+            self.labels.notification = format!("Can't reload: {error}");
+            return;
+        }
+        self.labels.update_all(&self.data.contents[self.data.offset..], self.data.endian);
+        adjust_offset(&mut self.data, &mut self.display, &mut self.labels);
+        self.labels.notification = String::from("Reloaded!");
+    }
+
+    /// Whether any of the open files has changes that weren't saved.
+    pub(crate) fn has_unsaved_changes(&self) -> bool {
+        self.data.has_unsaved_changes() || self.tabs.parked().any(AppData::has_unsaved_changes)
+    }
+
+    /// Opens the file at `path` in a new tab after the active one and switches to it.
+    ///
+    /// The file is opened the same way as the active one, i.e. only for reading if it is
+    /// read-only.
+    pub(crate) fn open_tab(&mut self, path: &str) {
+        let read_only = self.data.read_only;
+        let opened = OpenOptions::new().read(true).write(!read_only).open(path).and_then(|file| {
+            let map = read_only || file.metadata()?.len() > MAP_THRESHOLD;
+            AppData::open(file, None, map)
+        });
+        let (mut data, bom) = match opened {
+            Ok((data, _)) if data.contents.is_empty() => {
+                self.labels.notification = String::from("Can't open empty files!");
+                return;
+            }
+            Ok(opened) => opened,
+            Err(error) => {
+                self.labels.notification = format!("Can't open {path}: {error}");
+                return;
+            }
+        };
+        data.set_path(Path::new(path));
+        data.read_only = read_only;
+        data.opened_read_only = self.data.opened_read_only;
+        self.labels.notification = match bom {
+            Some(detected) => format!("{detected} BOM detected"),
+            None => format!("Opened {}!", data.name),
+        };
+        self.change_tab(|tabs, active| tabs.open(active, data));
+    }
+
+    /// Closes the active tab, switching to the next one. The last tab can't be closed.
+    pub(crate) fn close_tab(&mut self) {
+        let mut closed = None;
+        self.change_tab(|tabs, active| closed = tabs.close(active));
+        self.labels.notification = match closed {
+            Some(closed) => format!("Closed {}!", closed.name),
+            None => String::from("Can't close the only file!"),
+        };
+    }
+
+    /// Switches to the next (or previous) tab, wrapping around at the ends.
+    pub(crate) fn switch_tab(&mut self, forward: bool) {
+        self.change_tab(|tabs, active| if forward { tabs.next(active) } else { tabs.prev(active) });
+    }
+
+    fn change_tab(&mut self, change: impl FnOnce(&mut Tabs<AppData>, &mut AppData)) {
+        // The clipboard is shared by all tabs.
+        let clipboard = self.data.clipboard.take();
+        change(&mut self.tabs, &mut self.data);
+        self.data.clipboard = clipboard;
+
+        // The tab bar is only shown while there is more than one tab.
+        let shown = self.tabs.len() > 1;
+        self.display.set_tab_bar(&mut self.data, self.key_handler.as_ref(), shown);
+        self.focus_editor();
+        self.labels.update_all(&self.data.contents[self.data.offset..], self.data.endian);
+        adjust_offset(&mut self.data, &mut self.display, &mut self.labels);
     }
 
     /// Sets the current [`KeyHandler`]. This should be used when trying to focus another window.
@@ -215,34 +855,37 @@ impl Application {
     /// Popup dimensions are also changed here and are safe to do so because there are currently
     /// no popups that have dimensions based off of the size of the terminal frame.
     pub(crate) fn set_focused_window(&mut self, window: Window) {
-        match window {
-            Window::Hex => {
-                self.key_handler = Box::from(Editor::Hex);
-                self.data.editor = Editor::Hex;
-            }
-            Window::Ascii => {
-                self.key_handler = Box::from(Editor::Ascii);
-                self.data.editor = Editor::Ascii;
+        let popup: Box<dyn KeyHandler> = match window {
+            Window::Hex | Window::Ascii => {
+                let editor = if window == Window::Hex { Editor::Hex } else { Editor::Ascii };
+                self.key_handler = Box::from(editor);
+                self.data.editor = editor;
+                return;
             }
-            Window::JumpToByte => {
-                self.key_handler = Box::from(JumpToByte::new());
-                self.display.comp_layouts.popup = ScreenHandler::calculate_popup_dimensions(
-                    self.display.terminal_size,
-                    self.key_handler.as_ref(),
-                );
-            }
-            Window::UnsavedChanges => {
-                self.key_handler = Box::from(UnsavedChanges::new());
-                self.display.comp_layouts.popup = ScreenHandler::calculate_popup_dimensions(
-                    self.display.terminal_size,
-                    self.key_handler.as_ref(),
-                );
+            Window::BookmarkLabel => Box::from(BookmarkLabel::new()),
+            Window::BookmarkList => {
+                Box::from(BookmarkList::new(&self.data.bookmarks, self.data.offset))
             }
+            Window::Checksums => Box::from(Checksums::new(&self.data)),
+            Window::Entropy => Box::from(Entropy::new(&self.data, &self.display)),
+            Window::Export => Box::from(Export::new()),
+            Window::Fill => Box::from(Fill::new()),
+            Window::JumpToByte => Box::from(JumpToByte::new()),
+            Window::Search => Box::from(Search::new()),
+            Window::Strings => Box::from(Strings::new(&self.data)),
+            Window::SaveAs => Box::from(SaveAs::new()),
+            Window::OpenFile => Box::from(OpenFile::new()),
+            Window::CloseTab => Box::from(UnsavedChanges::closing_tab()),
+            Window::Reload => Box::from(Reload::new(self.data.has_unsaved_changes())),
+            Window::UnsavedChanges => Box::from(UnsavedChanges::new()),
             // We should never try and focus these windows to accept input.
             Window::Unhandled | Window::Label(_) => {
                 panic!()
             }
-        }
+        };
+        self.display.comp_layouts.popup =
+            ScreenHandler::calculate_popup_dimensions(self.display.terminal_size, popup.as_ref());
+        self.key_handler = popup;
     }
 
     /// Focuses the previously selected editor and is usually invoked after closing a popup.
@@ -250,3 +893,327 @@ impl Application {
         self.key_handler = Box::from(self.data.editor);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        fs::{self, OpenOptions},
+        process,
+    };
+
+    use super::*;
+
+    /// A file for a test to save to, which is removed once the test is done, whether it passed or
+    /// not.
+    struct TempFile {
+        path: PathBuf,
+    }
+
+    impl TempFile {
+        fn new(test: &str) -> Self {
+            let path = env::temp_dir().join(format!("heh-test-app-{test}-{}", process::id()));
+            Self { path }
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    /// The state for editing `contents`, along with the empty file it is saved to.
+    fn app_data(test: &str, contents: &[u8]) -> (AppData, TempFile) {
+        let temp = TempFile::new(test);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&temp.path)
+            .unwrap();
+        (AppData::new(file, contents.to_vec().into(), Encoding::Ascii), temp)
+    }
+
+    #[test]
+    fn test_selection_range() {
+        assert_eq!(Selection { anchor: 2, cursor: 5 }.range(), 2..6);
+        assert_eq!(Selection { anchor: 5, cursor: 2 }.range(), 2..6);
+        assert_eq!(Selection { anchor: 3, cursor: 3 }.range(), 3..4);
+
+        let (mut app, _temp) = app_data("selection_range", b"abcdef");
+        app.offset = 1;
+        assert_eq!(app.selection().range(), 1..2);
+        app.last_drag = Some(4);
+        assert_eq!(&app.contents[app.selection().range()], b"bcde");
+    }
+
+    #[test]
+    fn test_insert_byte() {
+        let (mut app, _temp) = app_data("insert_byte", b"abcdef");
+        app.offset = 2;
+        app.last_drag = Some(4);
+        app.search_match = Some(1..3);
+
+        app.insert_byte(0, b'X');
+        assert_eq!(app.contents, b"Xabcdef");
+        assert_eq!((app.offset, app.last_drag, app.search_match.clone()), (3, Some(5), Some(2..4)));
+
+        app.insert_byte(3, b'Y');
+        assert_eq!(app.contents, b"XabYcdef");
+        assert_eq!((app.offset, app.last_drag, app.search_match.clone()), (4, Some(6), Some(2..5)));
+
+        app.insert_byte(8, b'Z');
+        assert_eq!(app.contents, b"XabYcdefZ");
+        assert_eq!((app.offset, app.last_drag, app.search_match.clone()), (4, Some(6), Some(2..5)));
+
+        // Undoing and redoing an insert move everything back and forth with it.
+        app.edit(Action::Insert(1, b'W')).unwrap();
+        assert_eq!((app.offset, app.last_drag, app.search_match.clone()), (5, Some(7), Some(3..6)));
+        app.undo().unwrap();
+        assert_eq!(app.contents, b"XabYcdefZ");
+        assert_eq!((app.offset, app.last_drag, app.search_match.clone()), (4, Some(6), Some(2..5)));
+        app.redo().unwrap();
+        assert_eq!(app.contents, b"XWabYcdefZ");
+        assert_eq!((app.offset, app.last_drag, app.search_match), (5, Some(7), Some(3..6)));
+    }
+
+    #[test]
+    fn test_remove_byte() {
+        let (mut app, _temp) = app_data("remove_byte", b"abcdef");
+        app.offset = 3;
+        app.last_drag = Some(1);
+        app.search_match = Some(2..5);
+
+        assert_eq!(app.remove_byte(0), b'a');
+        assert_eq!(app.contents, b"bcdef");
+        assert_eq!((app.offset, app.last_drag, app.search_match.clone()), (2, Some(0), Some(1..4)));
+
+        // The cursor stays on the offset of the removed byte.
+        assert_eq!(app.remove_byte(2), b'd');
+        assert_eq!(app.contents, b"bcef");
+        assert_eq!((app.offset, app.last_drag, app.search_match.clone()), (2, Some(0), Some(1..3)));
+
+        app.offset = 3;
+        assert_eq!(app.remove_byte(3), b'f');
+        assert_eq!(app.contents, b"bce");
+        assert_eq!(app.offset, 2);
+
+        // Undoing and redoing a delete move everything back and forth with it.
+        app.search_match = Some(0..3);
+        app.edit(Action::Delete(1, b'c')).unwrap();
+        assert_eq!(app.contents, b"be");
+        assert_eq!((app.offset, app.last_drag, app.search_match.clone()), (1, Some(0), Some(0..2)));
+        app.undo().unwrap();
+        assert_eq!(app.contents, b"bce");
+        assert_eq!((app.offset, app.last_drag, app.search_match.clone()), (2, Some(0), Some(0..3)));
+        app.redo().unwrap();
+        assert_eq!(app.contents, b"be");
+        assert_eq!((app.offset, app.last_drag, app.search_match), (1, Some(0), Some(0..2)));
+    }
+
+    #[test]
+    fn test_read_only() {
+        let (mut app, _temp) = app_data("read_only", b"abc");
+        app.edit(Action::CharacterInput(0, b'a', b'X', None)).unwrap();
+        app.read_only = true;
+
+        let edits = [
+            Action::CharacterInput(1, b'b', b'Y', None),
+            Action::Insert(0, b'Z'),
+            Action::Backspace(1, b'b'),
+            Action::Delete(2, b'c'),
+        ];
+        for action in edits {
+            assert_eq!(app.edit(action), Err(EditError::ReadOnly));
+            assert_eq!(app.contents, b"Xbc");
+        }
+        assert_eq!(app.undo(), Err(EditError::ReadOnly));
+        assert_eq!(app.redo(), Err(EditError::ReadOnly));
+        assert_eq!(app.contents, b"Xbc");
+        assert_eq!(app.save().unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(app.file.metadata().unwrap().len(), 0);
+
+        app.toggle_read_only().unwrap();
+        assert_eq!(app.undo(), Ok(Some((0, None))));
+        assert_eq!(app.contents, b"abc");
+
+        // A file opened read-only can't be switched to be editable.
+        app.opened_read_only = true;
+        app.read_only = true;
+        assert_eq!(app.toggle_read_only(), Err(EditError::OpenedReadOnly));
+        assert!(app.read_only);
+    }
+
+    #[test]
+    fn test_edit_group() {
+        let (mut app, _temp) = app_data("edit_group", b"abcdef");
+        let actions =
+            (1..4).map(|offset| Action::CharacterInput(offset, app.contents[offset], 0, None));
+        app.edit_group(actions.collect()).unwrap();
+        assert_eq!(app.contents, b"a\0\0\0ef");
+
+        assert_eq!(app.undo(), Ok(Some((1, None))));
+        assert_eq!(app.contents, b"abcdef");
+        assert_eq!(app.undo(), Ok(None));
+
+        app.read_only = true;
+        assert_eq!(app.edit_group(vec![Action::Delete(0, b'a')]), Err(EditError::ReadOnly));
+        assert_eq!(app.contents, b"abcdef");
+    }
+
+    #[test]
+    fn test_bookmarks_follow_edits() {
+        let (mut app, _temp) = app_data("bookmarks_follow_edits", b"abcdef");
+        app.bookmarks.toggle(1, None);
+        app.bookmarks.toggle(4, Some(String::from("e")));
+
+        app.contents[4] = b'E';
+        app.insert_byte(2, b'X');
+        assert_eq!(app.bookmarks.iter().collect::<Vec<_>>(), vec![(1, None), (5, Some("e"))]);
+
+        app.remove_byte(1);
+        assert_eq!(app.bookmarks.iter().collect::<Vec<_>>(), vec![(4, Some("e"))]);
+        assert_eq!(app.contents[4], b'E');
+    }
+
+    #[test]
+    fn test_bookmarks_follow_undo() {
+        let (mut app, _temp) = app_data("bookmarks_follow_undo", b"abcdef");
+        app.bookmarks.toggle(4, None);
+        let offsets =
+            |app: &AppData| app.bookmarks.iter().map(|(offset, _)| offset).collect::<Vec<_>>();
+
+        app.edit(Action::Insert(1, b'X')).unwrap();
+        assert_eq!(offsets(&app), vec![5]);
+        app.undo().unwrap();
+        assert_eq!(offsets(&app), vec![4]);
+        app.redo().unwrap();
+        assert_eq!(offsets(&app), vec![5]);
+        app.undo().unwrap();
+
+        app.edit(Action::Delete(1, b'b')).unwrap();
+        assert_eq!(offsets(&app), vec![3]);
+        app.undo().unwrap();
+        assert_eq!(offsets(&app), vec![4]);
+        assert_eq!(app.contents[4], b'e');
+        app.redo().unwrap();
+        assert_eq!(offsets(&app), vec![3]);
+    }
+
+    #[test]
+    fn test_differing() {
+        let (mut app, _temp) = app_data("differing", b"abcdef");
+        app.compare_with(b"abXdef".to_vec());
+        assert_eq!(app.differing, 1);
+
+        app.edit(Action::CharacterInput(2, b'c', b'X', None)).unwrap();
+        assert_eq!(app.differing, 0);
+        app.edit(Action::CharacterInput(0, b'a', b'A', None)).unwrap();
+        assert_eq!(app.differing, 1);
+
+        // Every byte after an inserted one is compared to the one before it.
+        app.edit(Action::Insert(3, b'Y')).unwrap();
+        assert_eq!(app.contents, b"AbXYdef");
+        assert_eq!(app.differing, 5);
+        app.undo().unwrap();
+        assert_eq!(app.differing, 1);
+        app.undo().unwrap();
+        app.undo().unwrap();
+        assert_eq!(app.differing, 1);
+
+        // A group of edits is counted once all of them are made.
+        app.edit_group(vec![Action::Delete(0, b'a'), Action::Insert(0, b'a')]).unwrap();
+        assert_eq!(app.differing, 1);
+        app.undo().unwrap();
+        app.edit_group(vec![Action::Delete(5, b'f')]).unwrap();
+        assert_eq!(app.differing, 2);
+        app.undo().unwrap();
+        assert_eq!(app.differing, 1);
+    }
+
+    #[test]
+    fn test_dirty() {
+        let (mut app, _temp) = app_data("dirty", b"abcdef");
+        app.edit(Action::CharacterInput(1, b'b', b'B', None)).unwrap();
+        app.edit(Action::Insert(3, b'X')).unwrap();
+        assert_eq!(app.contents, b"aBcXdef");
+        assert!(app.is_dirty(1) && app.is_dirty(3));
+        assert!(!app.is_dirty(0) && !app.is_dirty(4));
+
+        // Undoing and redoing keep track of the modified bytes.
+        app.undo().unwrap();
+        assert!(app.is_dirty(1) && !app.is_dirty(3));
+        app.undo().unwrap();
+        assert!(!app.is_dirty(1) && !app.is_dirty(3));
+        app.redo().unwrap();
+        assert!(app.is_dirty(1));
+
+        app.mark_saved();
+        assert!(!app.is_dirty(1));
+        app.edit(Action::CharacterInput(1, b'B', b'b', None)).unwrap();
+        assert!(app.is_dirty(1));
+    }
+
+    #[test]
+    fn test_mapped_unsaved_changes() {
+        let temp = TempFile::new("mapped");
+        let path = &temp.path;
+        fs::write(path, b"abcdef").unwrap();
+        let file = OpenOptions::new().read(true).write(true).open(path).unwrap();
+        let (mut app, _) = AppData::open(file, None, true).unwrap();
+        assert!(matches!(app.contents, Contents::Mapped(_)));
+        assert_eq!(app.hashed_contents, None);
+        assert!(!app.has_unsaved_changes());
+
+        app.edit(Action::CharacterInput(1, b'b', b'B', None)).unwrap();
+        assert!(app.has_unsaved_changes());
+        app.edit(Action::CharacterInput(1, b'B', b'b', None)).unwrap();
+        assert!(!app.has_unsaved_changes());
+
+        // Removing a byte reads the contents into memory, which still aren't hashed.
+        app.edit(Action::Delete(5, b'f')).unwrap();
+        assert!(matches!(app.contents, Contents::Memory(_)));
+        assert!(app.has_unsaved_changes());
+    }
+
+    #[test]
+    fn test_reload() {
+        let temp = TempFile::new("reload");
+        let path = &temp.path;
+        fs::write(path, b"abcdefgh").unwrap();
+        let file = OpenOptions::new().read(true).write(true).open(path).unwrap();
+        let (mut app, _) = AppData::open(file, None, false).unwrap();
+        app.set_path(path);
+        app.edit(Action::CharacterInput(1, b'b', b'B', None)).unwrap();
+        app.offset = 6;
+        app.start_address = 4;
+        app.last_drag = Some(2);
+        app.bookmarks.toggle(1, None);
+        app.bookmarks.toggle(5, None);
+        app.compare_with(b"abcdefgh".to_vec());
+
+        // The cursor stays where it was as long as the file is long enough.
+        fs::write(path, b"ABCDEFGH").unwrap();
+        app.reload().unwrap();
+        assert_eq!(app.contents, b"ABCDEFGH");
+        assert_eq!((app.offset, app.start_address, app.last_drag), (6, 4, None));
+        assert_eq!(app.differing, 8);
+        assert!(!app.has_unsaved_changes() && !app.is_dirty(1));
+        assert_eq!(app.undo(), Ok(None));
+
+        // The cursor is clamped to the end of a file that shrank past it.
+        fs::write(path, b"xyz").unwrap();
+        app.reload().unwrap();
+        assert_eq!(app.contents, b"xyz");
+        assert_eq!((app.offset, app.start_address), (2, 0));
+        assert_eq!(app.bookmarks.iter().collect::<Vec<_>>(), vec![(1, None)]);
+        assert_eq!(app.differing, 8);
+
+        // heh can't edit empty files, so those aren't reloaded.
+        fs::write(path, b"").unwrap();
+        assert!(app.reload().is_err());
+        assert_eq!(app.contents, b"xyz");
+    }
+}
@@ -0,0 +1,168 @@
+//! Offsets in the file that the user has marked to come back to.
+
+use std::{collections::BTreeMap, ops::Range};
+
+/// A set of bookmarked offsets, each with an optional label.
+///
+/// The bookmarks move along with their bytes when bytes are inserted or removed before them, so
+/// they keep pointing at the same data while editing.
+#[derive(Default)]
+pub(crate) struct Bookmarks {
+    marks: BTreeMap<usize, Option<String>>,
+}
+
+impl Bookmarks {
+    /// Bookmarks `offset` with `label`, or removes the bookmark if there already is one.
+    ///
+    /// Returns whether `offset` is bookmarked afterwards.
+    pub(crate) fn toggle(&mut self, offset: usize, label: Option<String>) -> bool {
+        if self.marks.remove(&offset).is_some() {
+            false
+        } else {
+            self.marks.insert(offset, label);
+            true
+        }
+    }
+
+    pub(crate) fn contains(&self, offset: usize) -> bool {
+        self.marks.contains_key(&offset)
+    }
+
+    /// Whether any offset in `range` is bookmarked.
+    pub(crate) fn any_in(&self, range: Range<usize>) -> bool {
+        self.marks.range(range).next().is_some()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    /// The bookmarks in ascending order of their offsets.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, Option<&str>)> {
+        self.marks.iter().map(|(&offset, label)| (offset, label.as_deref()))
+    }
+
+    /// The first bookmark after `after`, wrapping around to the first one.
+    pub(crate) fn next(&self, after: usize) -> Option<usize> {
+        self.marks
+            .range(after.saturating_add(1)..)
+            .chain(&self.marks)
+            .next()
+            .map(|(&offset, _)| offset)
+    }
+
+    /// The last bookmark before `before`, wrapping around to the last one.
+    pub(crate) fn prev(&self, before: usize) -> Option<usize> {
+        self.marks
+            .range(..before)
+            .rev()
+            .chain(self.marks.iter().rev())
+            .next()
+            .map(|(&offset, _)| offset)
+    }
+
+    /// Removes the bookmarks at or after `len`, as the contents were cut down to that length.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.marks.split_off(&len);
+    }
+
+    /// Moves the bookmarks at or after `offset` along, as a byte was inserted there.
+    pub(crate) fn shift_insert(&mut self, offset: usize) {
+        let moved = self.marks.split_off(&offset);
+        self.marks.extend(moved.into_iter().map(|(marked, label)| (marked + 1, label)));
+    }
+
+    /// Moves the bookmarks after `offset` back, as the byte there was removed. A bookmark on the
+    /// removed byte is removed with it.
+    pub(crate) fn shift_remove(&mut self, offset: usize) {
+        let moved = self.marks.split_off(&offset);
+        let moved = moved.into_iter().filter(|&(marked, _)| marked != offset);
+        self.marks.extend(moved.map(|(marked, label)| (marked - 1, label)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marked(offsets: &[usize]) -> Bookmarks {
+        let mut bookmarks = Bookmarks::default();
+        for &offset in offsets {
+            bookmarks.toggle(offset, None);
+        }
+        bookmarks
+    }
+
+    fn offsets(bookmarks: &Bookmarks) -> Vec<usize> {
+        bookmarks.iter().map(|(offset, _)| offset).collect()
+    }
+
+    #[test]
+    fn test_toggle() {
+        let mut bookmarks = Bookmarks::default();
+        assert!(bookmarks.toggle(4, Some(String::from("header"))));
+        assert!(bookmarks.toggle(2, None));
+        assert_eq!(bookmarks.iter().collect::<Vec<_>>(), vec![(2, None), (4, Some("header"))]);
+        assert!(!bookmarks.toggle(4, None));
+        assert!(!bookmarks.contains(4));
+        assert!(bookmarks.any_in(0..3));
+        assert!(!bookmarks.any_in(3..10));
+    }
+
+    #[test]
+    fn test_next_prev() {
+        let bookmarks = marked(&[3, 10, 20]);
+        assert_eq!(bookmarks.next(0), Some(3));
+        assert_eq!(bookmarks.next(3), Some(10));
+        assert_eq!(bookmarks.next(15), Some(20));
+        assert_eq!(bookmarks.prev(20), Some(10));
+        assert_eq!(bookmarks.prev(4), Some(3));
+
+        // Both directions wrap around.
+        assert_eq!(bookmarks.next(20), Some(3));
+        assert_eq!(bookmarks.next(usize::MAX), Some(3));
+        assert_eq!(bookmarks.prev(3), Some(20));
+        assert_eq!(bookmarks.prev(0), Some(20));
+
+        // A single bookmark is found from itself.
+        let single = marked(&[5]);
+        assert_eq!(single.next(5), Some(5));
+        assert_eq!(single.prev(5), Some(5));
+        assert_eq!(Bookmarks::default().next(0), None);
+        assert_eq!(Bookmarks::default().prev(0), None);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut bookmarks = marked(&[0, 4, 5, 8]);
+        bookmarks.truncate(9);
+        assert_eq!(offsets(&bookmarks), vec![0, 4, 5, 8]);
+        bookmarks.truncate(5);
+        assert_eq!(offsets(&bookmarks), vec![0, 4]);
+    }
+
+    #[test]
+    fn test_shift_insert() {
+        let mut bookmarks = marked(&[0, 4, 8]);
+        bookmarks.shift_insert(4);
+        assert_eq!(offsets(&bookmarks), vec![0, 5, 9]);
+        bookmarks.shift_insert(0);
+        assert_eq!(offsets(&bookmarks), vec![1, 6, 10]);
+        bookmarks.shift_insert(11);
+        assert_eq!(offsets(&bookmarks), vec![1, 6, 10]);
+    }
+
+    #[test]
+    fn test_shift_remove() {
+        let mut bookmarks = marked(&[0, 4, 5, 8]);
+        bookmarks.toggle(5, None);
+        bookmarks.toggle(5, Some(String::from("kept")));
+        bookmarks.shift_remove(4);
+        assert_eq!(
+            bookmarks.iter().collect::<Vec<_>>(),
+            vec![(0, None), (4, Some("kept")), (7, None)]
+        );
+        bookmarks.shift_remove(1);
+        assert_eq!(offsets(&bookmarks), vec![0, 3, 6]);
+    }
+}
@@ -0,0 +1,233 @@
+//! The storage of the bytes under editing, which are either read into memory or mapped from the
+//! file.
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs::File,
+    io::{self, Read},
+    ops::{Deref, DerefMut, Range},
+};
+
+use memmap2::{MmapMut, MmapOptions};
+
+/// The size in bytes from which files are mapped into memory instead of read.
+pub(crate) const MAP_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// A sequence of bytes that can be read and overwritten, but not resized.
+pub(crate) trait Buffer {
+    fn as_slice(&self) -> &[u8];
+
+    fn as_mut_slice(&mut self) -> &mut [u8];
+
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The byte at `offset`, or `None` if it is out of bounds.
+    fn get(&self, offset: usize) -> Option<u8> {
+        self.as_slice().get(offset).copied()
+    }
+
+    /// The bytes in `range`, which must be in bounds.
+    fn slice(&self, range: Range<usize>) -> &[u8] {
+        &self.as_slice()[range]
+    }
+}
+
+impl Buffer for Vec<u8> {
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+/// A copy-on-write mapping of a file, so that only the parts that are viewed are read and
+/// overwritten bytes are kept in memory until they're saved.
+///
+/// The file must not be truncated by another program while it is mapped.
+pub(crate) struct MmapBuffer {
+    map: MmapMut,
+}
+
+impl MmapBuffer {
+    pub(crate) fn new(file: &File) -> io::Result<Self> {
+        // SAFETY: The mapping is private, so our writes don't reach the file, and heh never
+        // resizes the file while it is mapped. Other programs changing it are out of our control,
+        // as with any mapped file.
+        let map = unsafe { MmapOptions::new().map_copy(file)? };
+        Ok(Self { map })
+    }
+}
+
+impl Buffer for MmapBuffer {
+    fn as_slice(&self) -> &[u8] {
+        &self.map
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+}
+
+/// The contents of the file under editing.
+///
+/// Large files are mapped, which allows to view and overwrite them without reading them whole.
+/// Inserting or removing bytes reads a mapped file into memory first.
+pub(crate) enum Contents {
+    Memory(Vec<u8>),
+    Mapped(MmapBuffer),
+}
+
+impl Contents {
+    /// Reads the whole of `file` into memory, or maps it if `map` is set.
+    pub(crate) fn open(file: &mut File, map: bool) -> io::Result<Self> {
+        // Empty files can't be mapped, so they're read instead.
+        if map && file.metadata()?.len() > 0 {
+            return MmapBuffer::new(file).map(Contents::Mapped);
+        }
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        Ok(Contents::Memory(contents))
+    }
+
+    /// Inserts `byte` at `offset`, moving the bytes after it along.
+    pub(crate) fn insert(&mut self, offset: usize, byte: u8) {
+        self.memory().insert(offset, byte);
+    }
+
+    /// Removes the byte at `offset`, moving the bytes after it back.
+    pub(crate) fn remove(&mut self, offset: usize) -> u8 {
+        self.memory().remove(offset)
+    }
+
+    /// The contents in memory, reading them from the mapping first if needed.
+    fn memory(&mut self) -> &mut Vec<u8> {
+        if let Contents::Mapped(mapped) = self {
+            *self = Contents::Memory(mapped.as_slice().to_vec());
+        }
+        match self {
+            Contents::Memory(contents) => contents,
+            Contents::Mapped(_) => unreachable!("the contents were just read into memory"),
+        }
+    }
+}
+
+impl Buffer for Contents {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Contents::Memory(contents) => contents,
+            Contents::Mapped(mapped) => mapped.as_slice(),
+        }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        match self {
+            Contents::Memory(contents) => contents,
+            Contents::Mapped(mapped) => mapped.as_mut_slice(),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Contents {
+    fn from(contents: Vec<u8>) -> Self {
+        Contents::Memory(contents)
+    }
+}
+
+impl Default for Contents {
+    fn default() -> Self {
+        Contents::Memory(Vec::new())
+    }
+}
+
+impl Deref for Contents {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for Contents {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: AsRef<[u8]>> PartialEq<T> for Contents {
+    fn eq(&self, other: &T) -> bool {
+        self.as_slice() == other.as_ref()
+    }
+}
+
+impl Debug for Contents {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Contents::Memory(_) => "Memory",
+            Contents::Mapped(_) => "Mapped",
+        };
+        f.debug_tuple(kind).field(&self.as_slice()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::Write, process};
+
+    use super::*;
+
+    /// A deterministic byte at every offset, so that any offset can be checked.
+    #[allow(clippy::cast_possible_truncation)]
+    fn byte_at(offset: usize) -> u8 {
+        (offset.wrapping_mul(31) ^ (offset >> 11)) as u8
+    }
+
+    #[test]
+    fn test_large_file() {
+        let path = env::temp_dir().join(format!("heh-test-mmap-{}", process::id()));
+        let len = 8 * 1024 * 1024 + 3;
+        let bytes = (0..len).map(byte_at).collect::<Vec<_>>();
+        File::create(&path).unwrap().write_all(&bytes).unwrap();
+        drop(bytes);
+
+        let mut file = File::open(&path).unwrap();
+        let mut contents = Contents::open(&mut file, true).unwrap();
+        assert!(matches!(contents, Contents::Mapped(_)));
+        assert_eq!(contents.len(), len);
+        for offset in [0, 1, 4095, 4096, 1 << 20, 5_000_000, len - 1] {
+            assert_eq!(contents.get(offset), Some(byte_at(offset)), "{offset}");
+        }
+        assert_eq!(contents.get(len), None);
+        let expected = (4090..4100).map(byte_at).collect::<Vec<_>>();
+        assert_eq!(contents.slice(4090..4100), expected);
+
+        // Overwriting doesn't reach the file, and inserting reads it into memory.
+        contents[4096] = !byte_at(4096);
+        contents.insert(0, 0xAA);
+        assert!(matches!(contents, Contents::Memory(_)));
+        assert_eq!(contents.len(), len + 1);
+        assert_eq!(contents.get(4097), Some(!byte_at(4096)));
+        assert_eq!(contents.remove(len), byte_at(len - 1));
+        drop(file);
+        assert_eq!(fs::read(&path).unwrap()[4096], byte_at(4096));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_memory() {
+        let mut contents = Contents::from(b"abc".to_vec());
+        contents.insert(3, b'd');
+        assert_eq!(contents.remove(0), b'a');
+        contents[0] = b'B';
+        assert_eq!(contents, b"Bcd");
+        assert_eq!(contents.get(1), Some(b'c'));
+    }
+}
@@ -1,5 +1,7 @@
-use std::fmt::{Debug, Display, Formatter};
+use alloc::string::{String, ToString};
+use core::fmt::{self, Debug, Display, Formatter};
 
+#[cfg(feature = "std")]
 use tui::style::Color;
 
 pub(crate) const CHARACTER_NULL: char = '0';
@@ -8,32 +10,61 @@ pub(crate) const CHARACTER_CONTROL: char = '⍾';
 pub(crate) const CHARACTER_FILL: char = '•';
 pub(crate) const CHARACTER_UNKNOWN: char = '�';
 
+#[cfg(feature = "std")]
 const COLOR_NULL: Color = Color::DarkGray;
+#[cfg(feature = "std")]
 const COLOR_ASCII: Color = Color::Cyan;
+#[cfg(feature = "std")]
 const COLOR_UNICODE: Color = Color::LightCyan;
+#[cfg(feature = "std")]
 const COLOR_WHITESPACE: Color = Color::Green;
+#[cfg(feature = "std")]
 const COLOR_CONTROL: Color = Color::Magenta;
+#[cfg(feature = "std")]
 const COLOR_FILL: Color = Color::LightCyan;
+#[cfg(feature = "std")]
 const COLOR_UNKNOWN: Color = Color::Yellow;
 
-#[derive(Clone, Debug, PartialEq)]
-pub(crate) enum Type {
+/// The kind of character a [`Decoder`](crate::decoder::Decoder) produced, along with how many
+/// source bytes it was decoded from.
+///
+/// In the byte aligned view every byte takes up one terminal cell, so the size of a character is
+/// also the number of cells it occupies there.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Type {
+    /// A printable character decoded from a single byte.
     Ascii,
+    /// A control character decoded from a single byte, such as `\n` or the C1 controls of
+    /// Latin-1.
+    Control,
+    /// A character decoded from `n` bytes, which therefore occupies `n` cells in the aligned view.
     Unicode(usize),
+    /// A byte that could not be decoded.
     Unknown,
 }
 
 impl Type {
-    pub(crate) fn size(&self) -> usize {
+    /// The number of bytes the character was decoded from.
+    #[must_use]
+    pub fn size(&self) -> usize {
         match self {
-            Type::Ascii | Type::Unknown => 1,
+            Type::Ascii | Type::Control | Type::Unknown => 1,
             Type::Unicode(size) => *size,
         }
     }
 }
 
+/// Whether `character`, decoded as `typ`, is printable text.
+///
+/// Not all decoders report control characters as [`Type::Control`], as UTF-16 does for none of
+/// them, so `character` is checked as well.
+#[cfg(feature = "std")]
+pub(crate) fn is_printable(character: char, typ: &Type) -> bool {
+    !matches!(typ, Type::Control | Type::Unknown) && !character.is_control()
+}
+
 #[derive(Clone, Debug, PartialEq)]
-pub(crate) enum Category {
+pub enum Category {
     Null,
     Ascii,
     Unicode,
@@ -63,15 +94,14 @@ impl Category {
     pub(crate) fn escape(&self, character: char) -> char {
         match self {
             Category::Null => CHARACTER_NULL,
-            Category::Ascii | Category::Unicode => character,
+            Category::Ascii | Category::Unicode | Category::Fill | Category::Unknown => character,
             Category::Whitespace if character == ' ' => ' ',
             Category::Whitespace => CHARACTER_WHITESPACE,
             Category::Control => CHARACTER_CONTROL,
-            Category::Fill => CHARACTER_FILL,
-            Category::Unknown => CHARACTER_UNKNOWN,
         }
     }
 
+    #[cfg(feature = "std")]
     pub(crate) fn color(&self) -> &'static Color {
         match self {
             Category::Null => &COLOR_NULL,
@@ -85,28 +115,37 @@ impl Category {
     }
 }
 
-#[derive(Clone, Debug)]
-pub(crate) struct RichChar {
+#[derive(Clone, Debug, PartialEq)]
+pub struct RichChar {
     character: char,
     category: Category,
 }
 
 impl RichChar {
-    pub(crate) fn new(character: char, category: Category) -> Self {
+    #[must_use]
+    pub fn new(character: char, category: Category) -> Self {
         Self { character, category }
     }
 
-    pub(crate) fn escape(&self) -> char {
+    #[must_use]
+    pub fn escape(&self) -> char {
         self.category.escape(self.character)
     }
 
-    pub(crate) fn color(&self) -> &'static Color {
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn color(&self) -> &'static Color {
         self.category.color()
     }
+
+    #[must_use]
+    pub fn category(&self) -> &Category {
+        &self.category
+    }
 }
 
 impl Display for RichChar {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         Display::fmt(&self.character, f)
     }
 }
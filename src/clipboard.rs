@@ -0,0 +1,47 @@
+//! Access to the system clipboard.
+//!
+//! The clipboard is only available with the `clipboard` feature, so that heh can be built where
+//! there is no clipboard to link against. Without it, there is never a clipboard to be found.
+
+use std::error::Error;
+
+/// A handle to the system clipboard.
+#[cfg(feature = "clipboard")]
+pub(crate) struct Clipboard(arboard::Clipboard);
+
+#[cfg(feature = "clipboard")]
+impl Clipboard {
+    /// Connects to the system clipboard, returning `None` if there is none.
+    pub(crate) fn new() -> Option<Self> {
+        arboard::Clipboard::new().ok().map(Self)
+    }
+
+    /// Replaces the contents of the clipboard with `text`.
+    pub(crate) fn set_text(&mut self, text: String) -> Result<(), Box<dyn Error>> {
+        Ok(self.0.set_text(text)?)
+    }
+
+    /// The text on the clipboard.
+    pub(crate) fn get_text(&mut self) -> Result<String, Box<dyn Error>> {
+        Ok(self.0.get_text()?)
+    }
+}
+
+/// A handle to the system clipboard, which can't be created as heh is built without one.
+#[cfg(not(feature = "clipboard"))]
+pub(crate) enum Clipboard {}
+
+#[cfg(not(feature = "clipboard"))]
+impl Clipboard {
+    pub(crate) fn new() -> Option<Self> {
+        None
+    }
+
+    pub(crate) fn set_text(&mut self, _: String) -> Result<(), Box<dyn Error>> {
+        match *self {}
+    }
+
+    pub(crate) fn get_text(&mut self) -> Result<String, Box<dyn Error>> {
+        match *self {}
+    }
+}
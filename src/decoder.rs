@@ -1,159 +1,1615 @@
-use std::str::from_utf8;
+//! Decoders that turn raw bytes into displayable characters.
+
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+    str::from_utf8,
+};
+
+use unicode_width::UnicodeWidthChar;
 
 use crate::character::{Category, RichChar, Type, CHARACTER_FILL, CHARACTER_UNKNOWN};
 
-struct LossyASCIIDecoder<'a> {
+/// A lossy decoder that yields each decoded character together with its [`Type`].
+///
+/// Every byte of the input is accounted for: invalid bytes are decoded as [`Type::Unknown`]
+/// instead of being skipped, which allows a [`ByteAlignedDecoder`] to line the output up with
+/// the source bytes.
+pub trait Decoder: Iterator<Item = (char, Type)> {
+    /// The human readable name of the encoding, e.g. to be displayed in the UI.
+    fn name(&self) -> &'static str;
+
+    /// The number of bytes that have not been decoded yet.
+    fn remaining(&self) -> usize;
+
+    /// The offset of the byte the next character will be decoded from.
+    fn byte_offset(&self) -> usize;
+
+    /// Encodes `character` into the bytes it would be decoded from, or returns `None` if the
+    /// encoding cannot represent it.
+    fn encode_char(&self, character: char) -> Option<Vec<u8>>;
+
+    /// Wraps the decoder so that every character is yielded along with the range of bytes it was
+    /// decoded from.
+    fn ranged(self) -> RangedDecoder<Self>
+    where
+        Self: Sized,
+    {
+        RangedDecoder { decoder: self }
+    }
+}
+
+impl<D: Decoder + ?Sized> Decoder for Box<D> {
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+
+    fn remaining(&self) -> usize {
+        (**self).remaining()
+    }
+
+    fn byte_offset(&self) -> usize {
+        (**self).byte_offset()
+    }
+
+    fn encode_char(&self, character: char) -> Option<Vec<u8>> {
+        (**self).encode_char(character)
+    }
+}
+
+/// Yields the characters of a [`Decoder`] along with the range of bytes they were decoded from.
+///
+/// This is created by [`Decoder::ranged`].
+pub struct RangedDecoder<D: Decoder> {
+    decoder: D,
+}
+
+impl<D: Decoder> Iterator for RangedDecoder<D> {
+    type Item = (char, Type, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.decoder.byte_offset();
+        let (character, typ) = self.decoder.next()?;
+        Some((character, typ, start..self.decoder.byte_offset()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.decoder.size_hint()
+    }
+}
+
+/// Decodes bytes as ASCII, treating every byte above `0x7F` as unknown.
+pub struct LossyASCIIDecoder<'a> {
     bytes: &'a [u8],
     cursor: usize,
+    end: usize,
+    replacement: char,
+}
+
+impl<'a> LossyASCIIDecoder<'a> {
+    /// Creates a decoder that yields `replacement` instead of `�` for unknown bytes.
+    #[must_use]
+    pub fn with_replacement(bytes: &'a [u8], replacement: char) -> Self {
+        Self { bytes, cursor: 0, end: bytes.len(), replacement }
+    }
 }
 
 impl<'a> From<&'a [u8]> for LossyASCIIDecoder<'a> {
     fn from(bytes: &'a [u8]) -> Self {
-        Self { bytes, cursor: 0 }
+        Self::with_replacement(bytes, CHARACTER_UNKNOWN)
+    }
+}
+
+impl LossyASCIIDecoder<'_> {
+    fn decode(&self, byte: u8) -> (char, Type) {
+        if byte.is_ascii_control() {
+            (byte as char, Type::Control)
+        } else if byte.is_ascii() {
+            (byte as char, Type::Ascii)
+        } else {
+            (self.replacement, Type::Unknown)
+        }
     }
 }
 
-impl<'a> Iterator for LossyASCIIDecoder<'a> {
+impl Iterator for LossyASCIIDecoder<'_> {
     type Item = (char, Type);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.cursor < self.bytes.len() {
+        if self.cursor < self.end {
             let byte = self.bytes[self.cursor];
             self.cursor += 1;
-            if byte.is_ascii() {
-                Some((byte as char, Type::Ascii))
-            } else {
-                Some((CHARACTER_UNKNOWN, Type::Unknown))
-            }
+            Some(self.decode(byte))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl DoubleEndedIterator for LossyASCIIDecoder<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.cursor < self.end {
+            self.end -= 1;
+            Some(self.decode(self.bytes[self.end]))
         } else {
             None
         }
     }
 }
 
-struct LossyUTF8Decoder<'a> {
+impl Decoder for LossyASCIIDecoder<'_> {
+    fn name(&self) -> &'static str {
+        "ASCII"
+    }
+
+    fn remaining(&self) -> usize {
+        self.end - self.cursor
+    }
+
+    fn byte_offset(&self) -> usize {
+        self.cursor
+    }
+
+    fn encode_char(&self, character: char) -> Option<Vec<u8>> {
+        character.is_ascii().then(|| vec![character as u8])
+    }
+}
+
+/// Decodes bytes as UTF-8, treating every byte that is not part of a valid sequence as unknown.
+///
+/// Only the single byte controls are reported as [`Type::Control`], C1 controls take two bytes in
+/// UTF-8 and are reported as [`Type::Unicode`] to keep the size accurate.
+///
+/// When a sequence turns out to be invalid, only its lead byte is consumed and decoding resumes
+/// right after it. This way valid characters following a broken lead byte are never swallowed.
+pub struct LossyUTF8Decoder<'a> {
     bytes: &'a [u8],
     cursor: usize,
+    replacement: char,
+}
+
+impl<'a> LossyUTF8Decoder<'a> {
+    /// Creates a decoder that yields `replacement` instead of `�` for unknown bytes.
+    #[must_use]
+    pub fn with_replacement(bytes: &'a [u8], replacement: char) -> Self {
+        LossyUTF8Decoder { bytes, cursor: 0, replacement }
+    }
 }
 
 impl<'a> From<&'a [u8]> for LossyUTF8Decoder<'a> {
     fn from(bytes: &'a [u8]) -> Self {
-        LossyUTF8Decoder { bytes, cursor: 0 }
+        Self::with_replacement(bytes, CHARACTER_UNKNOWN)
     }
 }
 
-impl<'a> Iterator for LossyUTF8Decoder<'a> {
+impl Iterator for LossyUTF8Decoder<'_> {
     type Item = (char, Type);
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.cursor < self.bytes.len() {
             let typ = match self.bytes[self.cursor] {
-                0x00..=0x7F => Type::Ascii,
+                0x00..=0x1F | 0x7F => Type::Control,
+                0x20..=0x7E => Type::Ascii,
+                // These are only candidate lead bytes, overlong encodings such as `C0 80` are
+                // rejected by `from_utf8` below like any other invalid sequence.
                 0xC0..=0xDF => Type::Unicode(2),
                 0xE0..=0xEF => Type::Unicode(3),
                 0xF0..=0xF7 => Type::Unicode(4),
                 _ => {
                     self.cursor += 1;
-                    return Some((CHARACTER_UNKNOWN, Type::Unknown));
+                    return Some((self.replacement, Type::Unknown));
                 }
             };
 
-            let new_cursor = self.bytes.len().min(self.cursor + typ.size());
+            // A sequence that is cut off by the end of the buffer can never be valid, so only its
+            // lead byte is consumed and the remaining bytes are decoded on their own.
+            let new_cursor = self.cursor + typ.size();
+            if new_cursor > self.bytes.len() {
+                self.cursor += 1;
+                return Some((self.replacement, Type::Unknown));
+            }
             let chunk = &self.bytes[self.cursor..new_cursor];
 
-            if let Ok(mut chars) = from_utf8(chunk).map(str::chars) {
-                let char = chars.next().expect("the string must contain exactly one character");
-                debug_assert!(
-                    chars.next().is_none(),
-                    "the string must contain exactly one character"
-                );
+            if let Some(char) = first_char(chunk) {
                 self.cursor += typ.size();
                 Some((char, typ))
             } else {
                 self.cursor += 1;
-                Some((CHARACTER_UNKNOWN, Type::Unknown))
+                Some((self.replacement, Type::Unknown))
             }
         } else {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every character takes up between one and four bytes.
+        let remaining = self.remaining();
+        ((remaining + 3) / 4, Some(remaining))
+    }
 }
 
-#[derive(Copy, Clone, Debug)]
-pub(crate) enum Encoding {
-    Ascii,
-    Utf8,
+/// The first character of `chunk` if it is valid UTF-8.
+///
+/// The decoder only passes chunks of the size its lead byte announces, so a valid chunk is always
+/// exactly one character. Should that ever break, an empty chunk is decoded as unknown like an
+/// invalid one, and characters after the first are ignored while the decoder still advances past
+/// the whole chunk. Either way, no input can make the decoder panic, even in release builds.
+fn first_char(chunk: &[u8]) -> Option<char> {
+    from_utf8(chunk).ok()?.chars().next()
 }
 
-pub(crate) struct ByteAlignedDecoder<D: Iterator<Item = (char, Type)>> {
-    decoder: D,
-    to_fill: usize,
+impl Decoder for LossyUTF8Decoder<'_> {
+    fn name(&self) -> &'static str {
+        "UTF-8"
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.cursor
+    }
+
+    fn byte_offset(&self) -> usize {
+        self.cursor
+    }
+
+    fn encode_char(&self, character: char) -> Option<Vec<u8>> {
+        let mut buffer = [0; 4];
+        Some(character.encode_utf8(&mut buffer).as_bytes().to_vec())
+    }
 }
 
-type BoxedDecoder<'a> = Box<dyn Iterator<Item = (char, Type)> + 'a>;
+/// Decodes bytes as ISO-8859-1 (Latin-1), whose code points coincide with the first 256 Unicode
+/// code points.
+pub struct LossyLatin1Decoder<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+    end: usize,
+}
 
-impl<'a> ByteAlignedDecoder<BoxedDecoder<'a>> {
-    pub(crate) fn new(bytes: &'a [u8], encoding: Encoding) -> Self {
-        match encoding {
-            Encoding::Ascii => Box::new(LossyASCIIDecoder::from(bytes)) as BoxedDecoder,
-            Encoding::Utf8 => Box::new(LossyUTF8Decoder::from(bytes)) as BoxedDecoder,
+impl<'a> From<&'a [u8]> for LossyLatin1Decoder<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self { bytes, cursor: 0, end: bytes.len() }
+    }
+}
+
+impl LossyLatin1Decoder<'_> {
+    fn decode(byte: u8) -> (char, Type) {
+        let character = byte as char;
+        if character.is_control() {
+            (character, Type::Control)
+        } else {
+            (character, Type::Ascii)
         }
-        .into()
     }
 }
 
-impl<D: Iterator<Item = (char, Type)>> From<D> for ByteAlignedDecoder<D> {
-    fn from(decoder: D) -> Self {
-        Self { decoder, to_fill: 0 }
+impl Iterator for LossyLatin1Decoder<'_> {
+    type Item = (char, Type);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cursor < self.end {
+            let byte = self.bytes[self.cursor];
+            self.cursor += 1;
+            Some(Self::decode(byte))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
     }
 }
 
-impl<D: Iterator<Item = (char, Type)>> Iterator for ByteAlignedDecoder<D> {
-    type Item = RichChar;
+impl DoubleEndedIterator for LossyLatin1Decoder<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.cursor < self.end {
+            self.end -= 1;
+            Some(Self::decode(self.bytes[self.end]))
+        } else {
+            None
+        }
+    }
+}
+
+impl Decoder for LossyLatin1Decoder<'_> {
+    fn name(&self) -> &'static str {
+        "Latin-1"
+    }
+
+    fn remaining(&self) -> usize {
+        self.end - self.cursor
+    }
+
+    fn byte_offset(&self) -> usize {
+        self.cursor
+    }
+
+    fn encode_char(&self, character: char) -> Option<Vec<u8>> {
+        u8::try_from(u32::from(character)).ok().map(|byte| vec![byte])
+    }
+}
+
+/// The order in which the bytes of a multibyte value are stored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+impl Default for Endian {
+    fn default() -> Self {
+        Endian::Little
+    }
+}
+
+/// Decodes bytes as UTF-16 in the given byte order.
+///
+/// Characters of the basic multilingual plane take two bytes, characters made of a surrogate pair
+/// take four. Both bytes of a lone surrogate, as well as an odd trailing byte, are unknown.
+pub struct LossyUTF16Decoder<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+    endian: Endian,
+
+    /// The number of bytes that still belong to an invalid code unit.
+    invalid: usize,
+}
+
+impl<'a> LossyUTF16Decoder<'a> {
+    #[must_use]
+    pub fn new(bytes: &'a [u8], endian: Endian) -> Self {
+        Self { bytes, cursor: 0, endian, invalid: 0 }
+    }
+
+    /// Reads the code unit at the given offset, if there are two bytes left to read.
+    fn unit(&self, offset: usize) -> Option<u32> {
+        let unit = [*self.bytes.get(offset)?, *self.bytes.get(offset + 1)?];
+        Some(u32::from(match self.endian {
+            Endian::Little => u16::from_le_bytes(unit),
+            Endian::Big => u16::from_be_bytes(unit),
+        }))
+    }
+}
+
+impl Iterator for LossyUTF16Decoder<'_> {
+    type Item = (char, Type);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.to_fill == 0 {
-            let (character, typ) = self.decoder.next()?;
-            let category = match typ {
-                Type::Unknown => Category::Unknown,
-                _ => Category::from(&character),
-            };
-            self.to_fill = typ.size() - 1;
-            Some(RichChar::new(character, category))
+        if self.cursor >= self.bytes.len() {
+            return None;
+        }
+        if self.invalid == 0 {
+            match self.unit(self.cursor) {
+                Some(high @ 0xD800..=0xDBFF) => {
+                    if let Some(low @ 0xDC00..=0xDFFF) = self.unit(self.cursor + 2) {
+                        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                        if let Some(character) = char::from_u32(code) {
+                            self.cursor += 4;
+                            return Some((character, Type::Unicode(4)));
+                        }
+                    }
+                    self.invalid = 2;
+                }
+                Some(0xDC00..=0xDFFF) => self.invalid = 2,
+                Some(unit) => {
+                    if let Some(character) = char::from_u32(unit) {
+                        self.cursor += 2;
+                        return Some((character, Type::Unicode(2)));
+                    }
+                    self.invalid = 2;
+                }
+                None => self.invalid = 1,
+            }
+        }
+        self.invalid -= 1;
+        self.cursor += 1;
+        Some((CHARACTER_UNKNOWN, Type::Unknown))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every character takes up two or four bytes, unknown ones only a single byte.
+        let remaining = self.remaining();
+        ((remaining + 3) / 4, Some(remaining))
+    }
+}
+
+impl Decoder for LossyUTF16Decoder<'_> {
+    fn name(&self) -> &'static str {
+        match self.endian {
+            Endian::Little => "UTF-16LE",
+            Endian::Big => "UTF-16BE",
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.cursor
+    }
+
+    fn byte_offset(&self) -> usize {
+        self.cursor
+    }
+
+    fn encode_char(&self, character: char) -> Option<Vec<u8>> {
+        let mut buffer = [0; 2];
+        let units = character.encode_utf16(&mut buffer);
+        Some(
+            units
+                .iter()
+                .flat_map(|unit| match self.endian {
+                    Endian::Little => unit.to_le_bytes(),
+                    Endian::Big => unit.to_be_bytes(),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The characters of code page 437, indexed by their byte value.
+///
+/// The control range is mapped to the graphical glyphs of the original IBM PC character set, except
+/// for `0x00` which is kept as the null character.
+#[rustfmt::skip]
+const CP437: [char; 256] = [
+    '\0', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼',
+    '►', '◄', '↕', '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼',
+    ' ', '!', '"', '#', '$', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/',
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?',
+    '@', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O',
+    'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '[', '\\', ']', '^', '_',
+    '`', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '{', '|', '}', '~', '⌂',
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// Decodes bytes using code page 437, the character set of the original IBM PC.
+///
+/// Every byte maps to exactly one glyph, so no byte is ever unknown.
+pub struct LossyCP437Decoder<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+    end: usize,
+}
+
+impl<'a> From<&'a [u8]> for LossyCP437Decoder<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self { bytes, cursor: 0, end: bytes.len() }
+    }
+}
+
+impl LossyCP437Decoder<'_> {
+    fn decode(byte: u8) -> (char, Type) {
+        if byte == 0x00 {
+            (CP437[0], Type::Control)
         } else {
-            self.to_fill -= 1;
-            Some(RichChar::new(CHARACTER_FILL, Category::Fill))
+            (CP437[byte as usize], Type::Ascii)
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl Iterator for LossyCP437Decoder<'_> {
+    type Item = (char, Type);
 
-    const TEST_BYTES: &[u8] = b"text, controls \n \r\n, space \t, unicode \xC3\xA4h \xC3\xA0 la \xF0\x9F\x92\xA9, null \x00, invalid \xC0\xF8\xEE";
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cursor < self.end {
+            let byte = self.bytes[self.cursor];
+            self.cursor += 1;
+            Some(Self::decode(byte))
+        } else {
+            None
+        }
+    }
 
-    #[test]
-    fn test_decoder_ascii() {
-        let decoder = ByteAlignedDecoder::new(TEST_BYTES, Encoding::Ascii);
-        let characters: Vec<_> = decoder.collect();
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
 
-        assert_eq!(TEST_BYTES.len(), characters.len());
-        assert_eq!(
-            characters.iter().map(RichChar::escape).map(char::from).collect::<String>(),
-            "text, controls _ __, space _, unicode ��h �� la ����, null 0, invalid ���"
-        );
+impl DoubleEndedIterator for LossyCP437Decoder<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.cursor < self.end {
+            self.end -= 1;
+            Some(Self::decode(self.bytes[self.end]))
+        } else {
+            None
+        }
     }
+}
 
-    #[test]
-    fn test_decoder_utf8() {
-        let decoder = ByteAlignedDecoder::new(TEST_BYTES, Encoding::Utf8);
-        let characters: Vec<_> = decoder.collect();
+impl Decoder for LossyCP437Decoder<'_> {
+    fn name(&self) -> &'static str {
+        "CP437"
+    }
 
-        assert_eq!(TEST_BYTES.len(), characters.len());
-        assert_eq!(
-            characters.iter().map(RichChar::escape).map(char::from).collect::<String>(),
-            "text, controls _ __, space _, unicode ä•h à• la 💩•••, null 0, invalid ���"
-        );
+    fn remaining(&self) -> usize {
+        self.end - self.cursor
+    }
+
+    fn byte_offset(&self) -> usize {
+        self.cursor
+    }
+
+    fn encode_char(&self, character: char) -> Option<Vec<u8>> {
+        CP437
+            .iter()
+            .position(|&glyph| glyph == character)
+            .and_then(|byte| u8::try_from(byte).ok())
+            .map(|byte| vec![byte])
+    }
+}
+
+/// The characters of EBCDIC code page 037, indexed by their byte value.
+///
+/// The control positions are mapped to their Unicode control counterparts.
+#[rustfmt::skip]
+const CP037: [char; 256] = [
+    '\0', '\x01', '\x02', '\x03', '\u{9c}', '\x09', '\u{86}', '\x7f',
+    '\u{97}', '\u{8d}', '\u{8e}', '\x0b', '\x0c', '\x0d', '\x0e', '\x0f',
+    '\x10', '\x11', '\x12', '\x13', '\u{9d}', '\u{85}', '\x08', '\u{87}',
+    '\x18', '\x19', '\u{92}', '\u{8f}', '\x1c', '\x1d', '\x1e', '\x1f',
+    '\u{80}', '\u{81}', '\u{82}', '\u{83}', '\u{84}', '\x0a', '\x17', '\x1b',
+    '\u{88}', '\u{89}', '\u{8a}', '\u{8b}', '\u{8c}', '\x05', '\x06', '\x07',
+    '\u{90}', '\u{91}', '\x16', '\u{93}', '\u{94}', '\u{95}', '\u{96}', '\x04',
+    '\u{98}', '\u{99}', '\u{9a}', '\u{9b}', '\x14', '\x15', '\u{9e}', '\x1a',
+    ' ', '\u{a0}', 'â', 'ä', 'à', 'á', 'ã', 'å', 'ç', 'ñ', '¢', '.', '<', '(', '+', '|',
+    '&', 'é', 'ê', 'ë', 'è', 'í', 'î', 'ï', 'ì', 'ß', '!', '$', '*', ')', ';', '¬',
+    '-', '/', 'Â', 'Ä', 'À', 'Á', 'Ã', 'Å', 'Ç', 'Ñ', '¦', ',', '%', '_', '>', '?',
+    'ø', 'É', 'Ê', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', '`', ':', '#', '@', '\'', '=', '"',
+    'Ø', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', '«', '»', 'ð', 'ý', 'þ', '±',
+    '°', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 'ª', 'º', 'æ', '¸', 'Æ', '¤',
+    'µ', '~', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '¡', '¿', 'Ð', 'Ý', 'Þ', '®',
+    '^', '£', '¥', '·', '©', '§', '¶', '¼', '½', '¾', '[', ']', '¯', '¨', '´', '×',
+    '{', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', '\u{ad}', 'ô', 'ö', 'ò', 'ó', 'õ',
+    '}', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', '¹', 'û', 'ü', 'ù', 'ú', 'ÿ',
+    '\\', '÷', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '²', 'Ô', 'Ö', 'Ò', 'Ó', 'Õ',
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '³', 'Û', 'Ü', 'Ù', 'Ú', '\u{9f}',
+];
+
+/// Decodes bytes using EBCDIC code page 037, as found in IBM mainframe data.
+///
+/// Every byte maps to exactly one character, so no byte is ever unknown.
+pub struct LossyEBCDICDecoder<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+    end: usize,
+}
+
+impl<'a> From<&'a [u8]> for LossyEBCDICDecoder<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self { bytes, cursor: 0, end: bytes.len() }
+    }
+}
+
+impl LossyEBCDICDecoder<'_> {
+    fn decode(byte: u8) -> (char, Type) {
+        let character = CP037[byte as usize];
+        if character.is_control() {
+            (character, Type::Control)
+        } else {
+            (character, Type::Ascii)
+        }
+    }
+}
+
+impl Iterator for LossyEBCDICDecoder<'_> {
+    type Item = (char, Type);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cursor < self.end {
+            let byte = self.bytes[self.cursor];
+            self.cursor += 1;
+            Some(Self::decode(byte))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl DoubleEndedIterator for LossyEBCDICDecoder<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.cursor < self.end {
+            self.end -= 1;
+            Some(Self::decode(self.bytes[self.end]))
+        } else {
+            None
+        }
+    }
+}
+
+impl Decoder for LossyEBCDICDecoder<'_> {
+    fn name(&self) -> &'static str {
+        "EBCDIC"
+    }
+
+    fn remaining(&self) -> usize {
+        self.end - self.cursor
+    }
+
+    fn byte_offset(&self) -> usize {
+        self.cursor
+    }
+
+    fn encode_char(&self, character: char) -> Option<Vec<u8>> {
+        CP037
+            .iter()
+            .position(|&glyph| glyph == character)
+            .and_then(|byte| u8::try_from(byte).ok())
+            .map(|byte| vec![byte])
+    }
+}
+
+/// The encodings that can be used to display the file contents.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Ascii,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+    Cp437,
+    Ebcdic,
+}
+
+impl Encoding {
+    /// Every encoding, in the order they are cycled through.
+    pub const ALL: [Encoding; 7] = [
+        Encoding::Ascii,
+        Encoding::Utf8,
+        Encoding::Utf16Le,
+        Encoding::Utf16Be,
+        Encoding::Latin1,
+        Encoding::Cp437,
+        Encoding::Ebcdic,
+    ];
+
+    /// Creates the decoder for this encoding.
+    #[must_use]
+    pub fn decoder<'a>(&self, bytes: &'a [u8]) -> BoxedDecoder<'a> {
+        match self {
+            Encoding::Ascii => Box::new(LossyASCIIDecoder::from(bytes)),
+            Encoding::Utf8 => Box::new(LossyUTF8Decoder::from(bytes)),
+            Encoding::Utf16Le => Box::new(LossyUTF16Decoder::new(bytes, Endian::Little)),
+            Encoding::Utf16Be => Box::new(LossyUTF16Decoder::new(bytes, Endian::Big)),
+            Encoding::Latin1 => Box::new(LossyLatin1Decoder::from(bytes)),
+            Encoding::Cp437 => Box::new(LossyCP437Decoder::from(bytes)),
+            Encoding::Ebcdic => Box::new(LossyEBCDICDecoder::from(bytes)),
+        }
+    }
+
+    /// The encoding after this one, wrapping around after the last.
+    #[must_use]
+    pub fn next(self) -> Self {
+        // The encodings are listed in `ALL` in the order they are declared in.
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    /// The encoding before this one, wrapping around before the first.
+    #[must_use]
+    pub fn prev(self) -> Self {
+        Self::ALL[(self as usize + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Encodes `character` into the bytes it would be decoded from, or returns `None` if the
+    /// encoding cannot represent it.
+    #[must_use]
+    pub fn encode_char(self, character: char) -> Option<Vec<u8>> {
+        self.decoder(&[]).encode_char(character)
+    }
+}
+
+impl Display for Encoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.decoder(&[]).name())
+    }
+}
+
+/// Detects a byte order mark at the start of `bytes`, returning the encoding it signals along with
+/// its length in bytes.
+///
+/// The decoders never strip the byte order mark themselves, so every byte stays visible.
+#[must_use]
+pub fn detect_bom(bytes: &[u8]) -> Option<(Encoding, usize)> {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        Some((Encoding::Utf8, 3))
+    } else if bytes.starts_with(&[0xFF, 0xFE]) {
+        Some((Encoding::Utf16Le, 2))
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        Some((Encoding::Utf16Be, 2))
+    } else {
+        None
+    }
+}
+
+/// The range of bytes that the character containing the byte at `offset` is decoded from in
+/// `encoding`.
+///
+/// No character is longer than 4 bytes, so decoding starts 3 bytes before `offset`, rounded down to
+/// keep UTF-16 code units aligned. An `offset` past the end of `bytes` is a range of its own.
+#[must_use]
+pub fn char_span(bytes: &[u8], encoding: Encoding, offset: usize) -> Range<usize> {
+    let start = offset.saturating_sub(3) & !1;
+    let end = bytes.len().min(offset.saturating_add(4));
+    encoding
+        .decoder(bytes.get(start..end).unwrap_or_default())
+        .ranged()
+        .map(|(_, _, range)| start + range.start..start + range.end)
+        .find(|range| range.contains(&offset))
+        .unwrap_or(offset..offset + 1)
+}
+
+/// Wraps a [`Decoder`] so that exactly one character is yielded per byte.
+///
+/// Characters that span multiple bytes are followed by fill characters for their remaining bytes.
+pub struct ByteAlignedDecoder<D: Decoder> {
+    decoder: D,
+    to_fill: usize,
+    fill: char,
+
+    /// The character most recently decoded from the back, which is yielded after its fills.
+    back: Option<RichChar>,
+    back_to_fill: usize,
+}
+
+/// A [`Decoder`] for an encoding that is only known at runtime, see [`Encoding::decoder`].
+pub type BoxedDecoder<'a> = Box<dyn Decoder + 'a>;
+
+impl<'a> ByteAlignedDecoder<BoxedDecoder<'a>> {
+    #[must_use]
+    pub fn new(bytes: &'a [u8], encoding: Encoding) -> Self {
+        encoding.decoder(bytes).into()
+    }
+}
+
+impl<D: Decoder> ByteAlignedDecoder<D> {
+    /// Creates a decoder that pads multibyte characters with `fill` instead of `•`.
+    pub fn with_fill(decoder: D, fill: char) -> Self {
+        Self { decoder, to_fill: 0, fill, back: None, back_to_fill: 0 }
+    }
+
+    /// The name of the encoding used by the wrapped decoder.
+    pub fn name(&self) -> &'static str {
+        self.decoder.name()
+    }
+
+    /// The offset of the byte the next character belongs to. As exactly one character is yielded
+    /// per byte, this is also the number of characters yielded so far.
+    pub fn byte_offset(&self) -> usize {
+        self.decoder.byte_offset() - self.to_fill
+    }
+
+    /// Encodes `character` using the encoding of the wrapped decoder, see [`Decoder::encode_char`].
+    pub fn encode_char(&self, character: char) -> Option<Vec<u8>> {
+        self.decoder.encode_char(character)
+    }
+
+    fn fill(&self) -> RichChar {
+        RichChar::new(self.fill, Category::Fill)
+    }
+}
+
+impl<D: Decoder> From<D> for ByteAlignedDecoder<D> {
+    fn from(decoder: D) -> Self {
+        Self::with_fill(decoder, CHARACTER_FILL)
+    }
+}
+
+fn rich_char(character: char, typ: &Type) -> RichChar {
+    let category = match typ {
+        Type::Unknown => Category::Unknown,
+        _ => Category::from(&character),
+    };
+    RichChar::new(character, category)
+}
+
+impl<D: Decoder> Iterator for ByteAlignedDecoder<D> {
+    type Item = RichChar;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.to_fill > 0 {
+            self.to_fill -= 1;
+            Some(self.fill())
+        } else if let Some((character, typ)) = self.decoder.next() {
+            self.to_fill = typ.size() - 1;
+            Some(rich_char(character, &typ))
+        } else if let Some(character) = self.back.take() {
+            // The front caught up with a character that was started from the back.
+            Some(character)
+        } else if self.back_to_fill > 0 {
+            self.back_to_fill -= 1;
+            Some(self.fill())
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.decoder.remaining()
+            + self.to_fill
+            + self.back_to_fill
+            + usize::from(self.back.is_some());
+        (remaining, Some(remaining))
+    }
+}
+
+impl<D: Decoder + DoubleEndedIterator> DoubleEndedIterator for ByteAlignedDecoder<D> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.back_to_fill > 0 {
+            self.back_to_fill -= 1;
+            Some(self.fill())
+        } else if let Some(character) = self.back.take() {
+            Some(character)
+        } else if let Some((character, typ)) = self.decoder.next_back() {
+            // Walking backwards, the fills of a character come before the character itself.
+            self.back = Some(rich_char(character, &typ));
+            self.back_to_fill = typ.size() - 1;
+            self.next_back()
+        } else if self.to_fill > 0 {
+            self.to_fill -= 1;
+            Some(self.fill())
+        } else {
+            None
+        }
+    }
+}
+
+/// An item yielded by the [`RowDecoder`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum RowItem {
+    /// The character of a single source byte.
+    Char(RichChar),
+    /// The boundary between two rows, which does not belong to any source byte.
+    Newline,
+}
+
+/// Wraps a [`Decoder`] so that exactly one character is yielded per byte, with a
+/// [`RowItem::Newline`] between every `bytes_per_row` bytes.
+pub struct RowDecoder<D: Decoder> {
+    decoder: ByteAlignedDecoder<D>,
+    bytes_per_row: usize,
+    column: usize,
+}
+
+impl<D: Decoder> RowDecoder<D> {
+    /// Creates a decoder that splits the output of `decoder` into rows of `bytes_per_row` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_row` is 0.
+    pub fn new(decoder: D, bytes_per_row: usize) -> Self {
+        assert!(bytes_per_row > 0, "rows must contain at least one byte");
+        Self { decoder: decoder.into(), bytes_per_row, column: 0 }
+    }
+
+    /// Skips the first `count` bytes without counting them towards the first row. This allows
+    /// decoding to start before the first row, so that a character reaching into it is complete.
+    #[must_use]
+    pub fn skip_bytes(mut self, count: usize) -> Self {
+        for _ in 0..count {
+            self.decoder.next();
+        }
+        self
+    }
+
+    /// The offset of the byte the next character belongs to.
+    pub fn byte_offset(&self) -> usize {
+        self.decoder.byte_offset()
+    }
+}
+
+impl<D: Decoder> Iterator for RowDecoder<D> {
+    type Item = RowItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // A newline is only inserted between rows, never after the last one.
+        if self.column == self.bytes_per_row && self.decoder.size_hint().0 > 0 {
+            self.column = 0;
+            return Some(RowItem::Newline);
+        }
+        let character = self.decoder.next()?;
+        self.column += 1;
+        Some(RowItem::Char(character))
+    }
+}
+
+/// Decodes `bytes` into `out`, yielding exactly one character per byte like the
+/// [`ByteAlignedDecoder`].
+///
+/// The buffer is cleared first, so the same allocation can be reused for every frame.
+pub fn decode_into(bytes: &[u8], encoding: Encoding, out: &mut Vec<char>) {
+    out.clear();
+    out.extend(ByteAlignedDecoder::new(bytes, encoding).map(char::from));
+}
+
+/// Decodes `bytes`, yielding exactly one character per byte like the [`ByteAlignedDecoder`].
+///
+/// No input makes this panic, which makes it suitable as a fuzz target.
+#[must_use]
+pub fn decode_all(bytes: &[u8], encoding: Encoding) -> Vec<char> {
+    let mut out = Vec::with_capacity(bytes.len());
+    decode_into(bytes, encoding, &mut out);
+    out
+}
+
+/// Decodes `bytes` as text, or returns `None` if any of them can't be decoded.
+///
+/// Unlike [`decode_all`], every character is decoded once rather than once per byte, so the text
+/// encodes back to exactly `bytes`.
+#[must_use]
+pub fn decode_text(bytes: &[u8], encoding: Encoding) -> Option<String> {
+    encoding
+        .decoder(bytes)
+        .map(|(character, typ)| (typ != Type::Unknown).then(|| character))
+        .collect()
+}
+
+/// Wraps a [`Decoder`] so that every character is yielded along with the number of terminal cells
+/// it occupies.
+///
+/// Unlike the [`ByteAlignedDecoder`] nothing is padded, wide glyphs such as CJK characters report a
+/// width of 2 and combining marks as well as control characters a width of 0.
+pub struct WidthDecoder<D: Decoder> {
+    decoder: D,
+}
+
+impl<D: Decoder> WidthDecoder<D> {
+    /// The name of the encoding used by the wrapped decoder.
+    pub fn name(&self) -> &'static str {
+        self.decoder.name()
+    }
+}
+
+impl<D: Decoder> From<D> for WidthDecoder<D> {
+    fn from(decoder: D) -> Self {
+        Self { decoder }
+    }
+}
+
+impl<D: Decoder> Iterator for WidthDecoder<D> {
+    type Item = (char, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.decoder.next().map(|(character, _)| (character, character.width().unwrap_or(0)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.decoder.size_hint()
+    }
+}
+
+impl<D: Decoder + DoubleEndedIterator> DoubleEndedIterator for WidthDecoder<D> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.decoder.next_back().map(|(character, _)| (character, character.width().unwrap_or(0)))
+    }
+}
+
+/// Wraps a [`Decoder`] so that combining marks are grouped together with the character before
+/// them, yielding each cluster along with its [`Type`].
+///
+/// A cluster of several characters is reported as [`Type::Unicode`] of the combined size, so the
+/// size still matches the number of bytes it was decoded from. Control characters and unknown bytes
+/// never take on combining marks.
+pub struct GraphemeDecoder<D: Decoder> {
+    decoder: D,
+
+    /// The character that ended the previous cluster, which starts the next one.
+    pending: Option<(char, Type)>,
+}
+
+impl<D: Decoder> From<D> for GraphemeDecoder<D> {
+    fn from(decoder: D) -> Self {
+        Self { decoder, pending: None }
+    }
+}
+
+impl<'a> LossyUTF8Decoder<'a> {
+    /// Creates a decoder that groups combining marks with the character they belong to.
+    #[must_use]
+    pub fn with_graphemes(bytes: &'a [u8]) -> GraphemeDecoder<Self> {
+        Self::from(bytes).into()
+    }
+}
+
+/// Whether `character` is drawn on top of the character before it, such as `U+0301`.
+fn is_combining(character: char) -> bool {
+    // Control characters have no width at all, so they are not mistaken as combining.
+    character.width() == Some(0)
+}
+
+impl<D: Decoder> Iterator for GraphemeDecoder<D> {
+    type Item = (String, Type);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (base, typ) = self.pending.take().or_else(|| self.decoder.next())?;
+        let mut cluster = String::from(base);
+        if matches!(typ, Type::Control | Type::Unknown) {
+            return Some((cluster, typ));
+        }
+
+        let mut size = typ.size();
+        for (character, typ) in self.decoder.by_ref() {
+            if typ != Type::Unknown && is_combining(character) {
+                cluster.push(character);
+                size += typ.size();
+            } else {
+                self.pending = Some((character, typ));
+                break;
+            }
+        }
+
+        if cluster.chars().nth(1).is_some() {
+            Some((cluster, Type::Unicode(size)))
+        } else {
+            Some((cluster, typ))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // All remaining characters might end up in a single cluster.
+        let pending = usize::from(self.pending.is_some());
+        let (_, upper) = self.decoder.size_hint();
+        let lower = usize::from(pending > 0 || self.decoder.remaining() > 0);
+        (lower, upper.map(|upper| upper + pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    const TEST_BYTES: &[u8] = b"text, controls \n \r\n, space \t, unicode \xC3\xA4h \xC3\xA0 la \xF0\x9F\x92\xA9, null \x00, invalid \xC0\xF8\xEE";
+
+    #[test]
+    fn test_decoder_ascii() {
+        let decoder = ByteAlignedDecoder::new(TEST_BYTES, Encoding::Ascii);
+        let characters: Vec<_> = decoder.collect();
+
+        assert_eq!(TEST_BYTES.len(), characters.len());
+        assert_eq!(
+            characters.iter().map(RichChar::escape).collect::<String>(),
+            "text, controls _ __, space _, unicode ��h �� la ����, null 0, invalid ���"
+        );
+    }
+
+    #[test]
+    fn test_decoder_utf8() {
+        let decoder = ByteAlignedDecoder::new(TEST_BYTES, Encoding::Utf8);
+        let characters: Vec<_> = decoder.collect();
+
+        assert_eq!(TEST_BYTES.len(), characters.len());
+        assert_eq!(
+            characters.iter().map(RichChar::escape).collect::<String>(),
+            "text, controls _ __, space _, unicode ä•h à• la 💩•••, null 0, invalid ���"
+        );
+    }
+
+    #[test]
+    fn test_decoder_control() {
+        use Type::{Ascii, Control, Unicode, Unknown};
+
+        let bytes: &[u8] = b"a\n\t\x00\x7F\x85";
+        let types = |decoder: &mut dyn Decoder| decoder.map(|(_, typ)| typ).collect::<Vec<_>>();
+
+        let ascii = types(&mut LossyASCIIDecoder::from(bytes));
+        assert_eq!(ascii, [Ascii, Control, Control, Control, Control, Unknown]);
+
+        let utf8 = types(&mut LossyUTF8Decoder::from(bytes));
+        assert_eq!(utf8, [Ascii, Control, Control, Control, Control, Unknown]);
+
+        let latin1 = types(&mut LossyLatin1Decoder::from(bytes));
+        assert_eq!(latin1, [Ascii, Control, Control, Control, Control, Control]);
+
+        let c1 = types(&mut LossyUTF8Decoder::from(b"\xC2\x85".as_ref()));
+        assert_eq!(c1, [Unicode(2)]);
+    }
+
+    #[test]
+    fn test_decoder_utf8_truncated() {
+        let bytes: &[u8] = b"abc\xF0\x9F";
+        let decoded: Vec<_> = LossyUTF8Decoder::from(bytes).collect();
+        assert_eq!(
+            decoded,
+            [
+                ('a', Type::Ascii),
+                ('b', Type::Ascii),
+                ('c', Type::Ascii),
+                (CHARACTER_UNKNOWN, Type::Unknown),
+                (CHARACTER_UNKNOWN, Type::Unknown)
+            ]
+        );
+
+        let characters: String =
+            ByteAlignedDecoder::new(bytes, Encoding::Utf8).map(char::from).collect();
+        assert_eq!(characters.chars().count(), bytes.len());
+        assert_eq!(characters, "abc��");
+    }
+
+    #[test]
+    fn test_first_char() {
+        assert_eq!(first_char("ä".as_bytes()), Some('ä'));
+        assert_eq!(first_char(b"\xC3"), None);
+
+        // The decoder never passes these, but they must not panic either.
+        assert_eq!(first_char(b""), None);
+        assert_eq!(first_char(b"ab"), Some('a'));
+    }
+
+    #[test]
+    fn test_decoder_utf8_overlong() {
+        let bytes: &[u8] = b"\xC0\x80\xE0\x80\xAF\xF0\x80\x80\x80";
+        let decoded: Vec<_> = LossyUTF8Decoder::from(bytes).collect();
+        assert_eq!(decoded.len(), bytes.len());
+        assert!(decoded.iter().all(|decoded| decoded == &(CHARACTER_UNKNOWN, Type::Unknown)));
+    }
+
+    #[test]
+    fn test_decoder_utf8_resynchronize() {
+        let decoded: Vec<_> = LossyUTF8Decoder::from(b"\xE0\x28\xA4".as_ref()).collect();
+        assert_eq!(
+            decoded,
+            [
+                (CHARACTER_UNKNOWN, Type::Unknown),
+                ('(', Type::Ascii),
+                (CHARACTER_UNKNOWN, Type::Unknown)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_width_decoder() {
+        let bytes = "a\u{4E2D}e\u{301}\n".as_bytes();
+        let widths: Vec<_> = WidthDecoder::from(LossyUTF8Decoder::from(bytes)).collect();
+        assert_eq!(widths, [('a', 1), ('\u{4E2D}', 2), ('e', 1), ('\u{301}', 0), ('\n', 0)]);
+
+        let mut decoder = WidthDecoder::from(LossyLatin1Decoder::from(b"ab".as_ref()));
+        assert_eq!(decoder.name(), "Latin-1");
+        assert_eq!(decoder.next_back(), Some(('b', 1)));
+    }
+
+    #[test]
+    fn test_grapheme_decoder() {
+        let decoded: Vec<_> = LossyUTF8Decoder::with_graphemes(b"\x61\xCC\x81b").collect();
+        assert_eq!(
+            decoded,
+            [(String::from("a\u{301}"), Type::Unicode(3)), (String::from("b"), Type::Ascii)]
+        );
+
+        // Marks without a proper base character are left on their own.
+        let decoded: Vec<_> = LossyUTF8Decoder::with_graphemes(b"\n\xCC\x81\xFF").collect();
+        assert_eq!(
+            decoded,
+            [
+                (String::from("\n"), Type::Control),
+                (String::from("\u{301}"), Type::Unicode(2)),
+                (String::from(CHARACTER_UNKNOWN), Type::Unknown)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_into() {
+        let mut out = Vec::new();
+        decode_into(TEST_BYTES, Encoding::Utf8, &mut out);
+        let first = out.clone();
+        let capacity = out.capacity();
+        assert_eq!(out.len(), TEST_BYTES.len());
+
+        decode_into(TEST_BYTES, Encoding::Utf8, &mut out);
+        assert_eq!(out, first);
+        assert_eq!(out.capacity(), capacity);
+    }
+
+    /// A xorshift generator with a fixed seed, so that the random tests are reproducible.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Random bytes, mostly drawn from the ranges that are special in some encoding.
+        fn bytes(&mut self, len: usize) -> Vec<u8> {
+            (0..len)
+                .map(|_| {
+                    let byte = self.next().to_le_bytes()[0];
+                    match self.next() % 4 {
+                        // UTF-8 continuation bytes and UTF-16 surrogates.
+                        0 => 0x80 | (byte & 0x3F),
+                        1 => 0xD8 | (byte & 0x07),
+                        _ => byte,
+                    }
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_decode_all_random() {
+        let mut random = XorShift(0x2545_F491_4F6C_DD1D);
+        for _ in 0..500 {
+            let len = (random.next() % 64) as usize;
+            let bytes = random.bytes(len);
+            for encoding in Encoding::ALL {
+                assert_eq!(
+                    decode_all(&bytes, encoding).len(),
+                    bytes.len(),
+                    "{encoding}: {bytes:X?}"
+                );
+            }
+        }
+        for encoding in Encoding::ALL {
+            assert!(decode_all(b"", encoding).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_decode_text() {
+        assert_eq!(decode_text(b"heh\n", Encoding::Ascii), Some("heh\n".to_string()));
+        assert_eq!(decode_text(&[0xE2, 0x82, 0xAC], Encoding::Utf8), Some("€".to_string()));
+        assert_eq!(decode_text(&[0xE9], Encoding::Latin1), Some("é".to_string()));
+        assert_eq!(decode_text(b"", Encoding::Utf16Le), Some(String::new()));
+
+        // A single undecodable byte fails the whole text.
+        assert_eq!(decode_text(b"ab\xFF", Encoding::Ascii), None);
+        assert_eq!(decode_text(&[0xE2, 0x82], Encoding::Utf8), None);
+        assert_eq!(decode_text(&[b'a', 0, b'b'], Encoding::Utf16Le), None);
+    }
+
+    #[test]
+    fn test_row_decoder() {
+        let decoder = RowDecoder::new(LossyASCIIDecoder::from(b"0123456789".as_ref()), 4);
+        let text: String = decoder
+            .map(|item| match item {
+                RowItem::Char(character) => char::from(character),
+                RowItem::Newline => '\n',
+            })
+            .collect();
+        assert_eq!(text, "0123\n4567\n89");
+
+        let decoder = RowDecoder::new(LossyASCIIDecoder::from(b"01234567".as_ref()), 4);
+        let newlines: Vec<_> = decoder
+            .enumerate()
+            .filter_map(|(i, item)| (item == RowItem::Newline).then(|| i))
+            .collect();
+        assert_eq!(newlines, [4]);
+    }
+
+    #[test]
+    fn test_row_decoder_skip_bytes() {
+        let decoder = RowDecoder::new(LossyUTF8Decoder::from("aä1234".as_bytes()), 2).skip_bytes(2);
+        assert_eq!(decoder.byte_offset(), 2);
+        let items: Vec<_> = decoder.collect();
+        assert_eq!(items.len(), 7);
+        assert_eq!(items[0], RowItem::Char(RichChar::new(CHARACTER_FILL, Category::Fill)));
+        assert_eq!(items[2], RowItem::Newline);
+        assert_eq!(items[5], RowItem::Newline);
+        assert_eq!(items[6], RowItem::Char(RichChar::new('4', Category::Ascii)));
+    }
+
+    #[test]
+    fn test_decoder_size_hint() {
+        let mut decoder = LossyASCIIDecoder::from(TEST_BYTES);
+        assert_eq!(decoder.size_hint(), (TEST_BYTES.len(), Some(TEST_BYTES.len())));
+        decoder.next();
+        assert_eq!(decoder.size_hint(), (TEST_BYTES.len() - 1, Some(TEST_BYTES.len() - 1)));
+
+        let decoder = LossyUTF8Decoder::from(TEST_BYTES);
+        assert_eq!(decoder.size_hint(), ((TEST_BYTES.len() + 3) / 4, Some(TEST_BYTES.len())));
+
+        // Stop right after the ä, so that its fill character is still pending.
+        let consumed = TEST_BYTES.iter().position(|&byte| byte == 0xC3).unwrap() + 1;
+        let mut decoder = ByteAlignedDecoder::new(TEST_BYTES, Encoding::Utf8);
+        decoder.by_ref().take(consumed).for_each(drop);
+        let remaining = TEST_BYTES.len() - consumed;
+        assert_eq!(decoder.size_hint(), (remaining, Some(remaining)));
+        assert_eq!(decoder.count(), remaining);
+    }
+
+    #[test]
+    fn test_decoder_reverse() {
+        let forward: Vec<_> = LossyASCIIDecoder::from(TEST_BYTES).collect();
+        let mut backward: Vec<_> = LossyASCIIDecoder::from(TEST_BYTES).rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        let forward: String =
+            ByteAlignedDecoder::from(LossyASCIIDecoder::from(TEST_BYTES)).map(char::from).collect();
+        let backward: String = ByteAlignedDecoder::from(LossyASCIIDecoder::from(TEST_BYTES))
+            .rev()
+            .map(char::from)
+            .collect();
+        assert_eq!(forward, backward.chars().rev().collect::<String>());
+    }
+
+    #[test]
+    fn test_decoder_reverse_meets_forward() {
+        // None of the double ended decoders yield multibyte characters, so this one pretends to in
+        // order to check that fills pending on one end are picked up by the other one.
+        struct Wide<'a>(LossyLatin1Decoder<'a>);
+        impl Iterator for Wide<'_> {
+            type Item = (char, Type);
+            fn next(&mut self) -> Option<Self::Item> {
+                self.0.next().map(|(character, _)| (character, Type::Unicode(2)))
+            }
+        }
+        impl DoubleEndedIterator for Wide<'_> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.0.next_back().map(|(character, _)| (character, Type::Unicode(2)))
+            }
+        }
+        impl Decoder for Wide<'_> {
+            fn name(&self) -> &'static str {
+                "Wide"
+            }
+            fn remaining(&self) -> usize {
+                self.0.remaining() * 2
+            }
+            fn byte_offset(&self) -> usize {
+                self.0.byte_offset() * 2
+            }
+            fn encode_char(&self, character: char) -> Option<Vec<u8>> {
+                self.0.encode_char(character)
+            }
+        }
+
+        let mut decoder = ByteAlignedDecoder::from(Wide(LossyLatin1Decoder::from(b"ab".as_ref())));
+        assert_eq!(decoder.size_hint(), (4, Some(4)));
+        assert_eq!(decoder.next().map(char::from), Some('a'));
+        assert_eq!(decoder.next_back().map(char::from), Some(CHARACTER_FILL));
+        assert_eq!(decoder.next_back().map(char::from), Some('b'));
+        assert_eq!(decoder.next_back().map(char::from), Some(CHARACTER_FILL));
+        assert_eq!(decoder.next_back().map(char::from), None);
+        assert_eq!(decoder.next().map(char::from), None);
+
+        let mut decoder = ByteAlignedDecoder::from(Wide(LossyLatin1Decoder::from(b"ab".as_ref())));
+        assert_eq!(decoder.next_back().map(char::from), Some(CHARACTER_FILL));
+        assert_eq!(decoder.next().map(char::from), Some('a'));
+        assert_eq!(decoder.next().map(char::from), Some(CHARACTER_FILL));
+        assert_eq!(decoder.next().map(char::from), Some('b'));
+        assert_eq!(decoder.next().map(char::from), None);
+    }
+
+    #[test]
+    fn test_decoder_byte_offset() {
+        let start = TEST_BYTES.iter().position(|&byte| byte == 0xF0).unwrap();
+        let ranges: Vec<_> = LossyUTF8Decoder::from(TEST_BYTES).ranged().collect();
+        assert!(ranges.contains(&('💩', Type::Unicode(4), start..start + 4)));
+        assert!(ranges.windows(2).all(|pair| pair[0].2.end == pair[1].2.start));
+        assert_eq!(ranges.last().unwrap().2.end, TEST_BYTES.len());
+
+        let mut decoder = ByteAlignedDecoder::new(TEST_BYTES, Encoding::Utf8);
+        decoder.by_ref().take(start).for_each(drop);
+        let offsets: Vec<_> = (0..4)
+            .map(|_| (decoder.byte_offset(), decoder.next().map(char::from).unwrap()))
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                (start, '💩'),
+                (start + 1, CHARACTER_FILL),
+                (start + 2, CHARACTER_FILL),
+                (start + 3, CHARACTER_FILL)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decoder_replacement() {
+        let characters: String =
+            ByteAlignedDecoder::from(LossyUTF8Decoder::with_replacement(TEST_BYTES, '.'))
+                .map(|character| character.escape())
+                .collect();
+        assert_eq!(TEST_BYTES.len(), characters.chars().count());
+        assert!(characters.ends_with(", invalid ..."));
+
+        let characters: String =
+            ByteAlignedDecoder::from(LossyASCIIDecoder::with_replacement(b"\xC0\xF8\xEEa", '.'))
+                .map(|character| character.escape())
+                .collect();
+        assert_eq!(characters, "...a");
+    }
+
+    #[test]
+    fn test_decoder_fill() {
+        let decoder = ByteAlignedDecoder::with_fill(
+            LossyUTF8Decoder::from(b"\xF0\x9F\x92\xA9!".as_ref()),
+            ' ',
+        );
+        let characters: String = decoder.map(|character| character.escape()).collect();
+        assert_eq!(characters.chars().count(), 5);
+        assert_eq!(characters, "💩   !");
+    }
+
+    #[test]
+    fn test_decoder_utf16() {
+        let bytes: &[u8] = b"O\x00K\x00\x3D\xD8\xA9\xDC\x00\xDC!";
+        let decoder = ByteAlignedDecoder::new(bytes, Encoding::Utf16Le);
+        let characters: Vec<_> = decoder.collect();
+
+        assert_eq!(bytes.len(), characters.len());
+        assert_eq!(characters.iter().map(RichChar::escape).collect::<String>(), "O•K•💩•••���");
+
+        let bytes: &[u8] = b"\x00O\x00K\xD8\x3D\xDC\xA9\xD8\x3D";
+        let lossy: Vec<_> = LossyUTF16Decoder::new(bytes, Endian::Big).collect();
+        assert_eq!(
+            lossy,
+            [
+                ('O', Type::Unicode(2)),
+                ('K', Type::Unicode(2)),
+                ('💩', Type::Unicode(4)),
+                (CHARACTER_UNKNOWN, Type::Unknown),
+                (CHARACTER_UNKNOWN, Type::Unknown)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decoder_latin1() {
+        let decoder = ByteAlignedDecoder::new(TEST_BYTES, Encoding::Latin1);
+        let characters: Vec<_> = decoder.collect();
+
+        assert_eq!(TEST_BYTES.len(), characters.len());
+        assert!(characters.iter().all(|character| character.escape() != CHARACTER_FILL));
+
+        let decoder = ByteAlignedDecoder::new(b"\xE4\xE0\xF6", Encoding::Latin1);
+        assert_eq!(decoder.map(char::from).collect::<String>(), "äàö");
+    }
+
+    #[test]
+    fn test_decoder_cp437() {
+        let decoder = ByteAlignedDecoder::new(TEST_BYTES, Encoding::Cp437);
+        let characters: Vec<_> = decoder.collect();
+
+        assert_eq!(TEST_BYTES.len(), characters.len());
+        assert_eq!(
+            characters.iter().map(RichChar::escape).collect::<String>(),
+            "text, controls ◙ ♪◙, space ○, unicode ├ñh ├á la ≡ƒÆ⌐, null 0, invalid └°ε"
+        );
+
+        let decoder = ByteAlignedDecoder::new(b"\xB0\xDB\x03", Encoding::Cp437);
+        assert_eq!(decoder.map(char::from).collect::<String>(), "░█♥");
+    }
+
+    #[test]
+    fn test_decoder_ebcdic() {
+        let decoder = ByteAlignedDecoder::new(TEST_BYTES, Encoding::Ebcdic);
+        assert_eq!(decoder.count(), TEST_BYTES.len());
+
+        let decoder = ByteAlignedDecoder::new(b"\xC8\xC5\xD3\xD3\xD6", Encoding::Ebcdic);
+        assert_eq!(decoder.map(char::from).collect::<String>(), "HELLO");
+
+        let lossy: Vec<_> = LossyEBCDICDecoder::from(b"\x25\x40\xF1".as_ref()).collect();
+        assert_eq!(lossy, [('\n', Type::Control), (' ', Type::Ascii), ('1', Type::Ascii)]);
+    }
+
+    #[test]
+    fn test_encode_char() {
+        assert_eq!(Encoding::Ascii.encode_char('a'), Some(vec![b'a']));
+        assert_eq!(Encoding::Ascii.encode_char('é'), None);
+
+        assert_eq!(Encoding::Utf8.encode_char('a'), Some(vec![b'a']));
+        assert_eq!(Encoding::Utf8.encode_char('é'), Some(vec![0xC3, 0xA9]));
+        assert_eq!(Encoding::Utf8.encode_char('😀'), Some(vec![0xF0, 0x9F, 0x98, 0x80]));
+
+        assert_eq!(Encoding::Utf16Le.encode_char('é'), Some(vec![0xE9, 0x00]));
+        assert_eq!(Encoding::Utf16Be.encode_char('é'), Some(vec![0x00, 0xE9]));
+        assert_eq!(Encoding::Utf16Le.encode_char('😀'), Some(vec![0x3D, 0xD8, 0x00, 0xDE]));
+        assert_eq!(Encoding::Utf16Be.encode_char('😀'), Some(vec![0xD8, 0x3D, 0xDE, 0x00]));
+
+        assert_eq!(Encoding::Latin1.encode_char('é'), Some(vec![0xE9]));
+        assert_eq!(Encoding::Latin1.encode_char('€'), None);
+
+        assert_eq!(Encoding::Cp437.encode_char('é'), Some(vec![0x82]));
+        assert_eq!(Encoding::Cp437.encode_char('☺'), Some(vec![0x01]));
+        assert_eq!(Encoding::Cp437.encode_char('€'), None);
+
+        assert_eq!(Encoding::Ebcdic.encode_char('H'), Some(vec![0xC8]));
+        assert_eq!(Encoding::Ebcdic.encode_char('€'), None);
+    }
+
+    #[test]
+    fn test_encode_char_round_trip() {
+        for encoding in Encoding::ALL {
+            for character in ['a', 'Z', '~', 'é', '░', 'Ω', '😀'] {
+                if let Some(bytes) = encoding.encode_char(character) {
+                    let mut decoder = ByteAlignedDecoder::new(&bytes, encoding);
+                    assert_eq!(decoder.next().map(char::from), Some(character), "{encoding:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_bom() {
+        assert!(matches!(detect_bom(b"\xEF\xBB\xBFabc"), Some((Encoding::Utf8, 3))));
+        assert!(matches!(detect_bom(b"\xFF\xFEa\x00"), Some((Encoding::Utf16Le, 2))));
+        assert!(matches!(detect_bom(b"\xFE\xFF\x00a"), Some((Encoding::Utf16Be, 2))));
+        assert!(detect_bom(b"\xEF\xBB").is_none());
+        assert!(detect_bom(TEST_BYTES).is_none());
+        assert!(detect_bom(b"").is_none());
+    }
+
+    #[test]
+    fn test_char_span() {
+        // Every byte of a multi-byte character maps back to where the character starts.
+        let bytes = "a€b".as_bytes();
+        for offset in 1..4 {
+            assert_eq!(char_span(bytes, Encoding::Utf8, offset), 1..4);
+        }
+        assert_eq!(char_span(bytes, Encoding::Utf8, 0), 0..1);
+        assert_eq!(char_span(bytes, Encoding::Utf8, 4), 4..5);
+        assert_eq!(char_span(bytes, Encoding::Ascii, 2), 2..3);
+
+        // Decoding starts well before the character, even if that is mid-sequence.
+        let bytes = "€𝄞".as_bytes();
+        assert_eq!(char_span(bytes, Encoding::Utf8, 5), 3..7);
+        assert_eq!(char_span(bytes, Encoding::Utf8, 6), 3..7);
+
+        // Surrogate pairs span both code units.
+        let bytes = b"a\x00\x34\xD8\x1E\xDD";
+        assert_eq!(char_span(bytes, Encoding::Utf16Le, 1), 0..2);
+        assert_eq!(char_span(bytes, Encoding::Utf16Le, 4), 2..6);
+        assert_eq!(char_span(bytes, Encoding::Utf16Le, 5), 2..6);
+
+        assert_eq!(char_span(b"", Encoding::Utf8, 0), 0..1);
+    }
+
+    #[test]
+    fn test_encoding_decoder() {
+        assert!(Encoding::Utf8.decoder(TEST_BYTES).eq(LossyUTF8Decoder::from(TEST_BYTES)));
+        assert!(Encoding::Cp437.decoder(TEST_BYTES).eq(LossyCP437Decoder::from(TEST_BYTES)));
+        for encoding in Encoding::ALL {
+            assert_eq!(encoding.to_string(), encoding.decoder(TEST_BYTES).name());
+        }
+    }
+
+    #[test]
+    fn test_encoding_cycle() {
+        assert_eq!(Encoding::Ascii.next(), Encoding::Utf8);
+        assert_eq!(Encoding::Ascii.prev(), Encoding::Ebcdic);
+        assert_eq!(Encoding::Ebcdic.next(), Encoding::Ascii);
+        for (index, encoding) in Encoding::ALL.into_iter().enumerate() {
+            assert_eq!(encoding as usize, index);
+            assert_eq!(encoding.next().prev(), encoding);
+        }
+    }
+
+    #[test]
+    fn test_decoder_name() {
+        assert_eq!(LossyASCIIDecoder::from(TEST_BYTES).name(), "ASCII");
+        assert_eq!(LossyUTF8Decoder::from(TEST_BYTES).name(), "UTF-8");
+        assert_eq!(LossyUTF16Decoder::new(TEST_BYTES, Endian::Little).name(), "UTF-16LE");
+        assert_eq!(LossyUTF16Decoder::new(TEST_BYTES, Endian::Big).name(), "UTF-16BE");
+        assert_eq!(LossyLatin1Decoder::from(TEST_BYTES).name(), "Latin-1");
+        assert_eq!(LossyCP437Decoder::from(TEST_BYTES).name(), "CP437");
+        assert_eq!(LossyEBCDICDecoder::from(TEST_BYTES).name(), "EBCDIC");
+        assert_eq!(ByteAlignedDecoder::new(TEST_BYTES, Encoding::Utf8).name(), "UTF-8");
+
+        let boxed: Box<dyn Decoder> = Box::new(LossyUTF8Decoder::from(TEST_BYTES));
+        assert_eq!(ByteAlignedDecoder::from(boxed).count(), TEST_BYTES.len());
     }
 }
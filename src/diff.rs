@@ -0,0 +1,56 @@
+//! Comparing the file under editing to another one.
+//!
+//! The files are compared byte by byte at the same offsets, so inserting or removing a byte makes
+//! every byte after it differ.
+
+/// The number of offsets at which `a` and `b` differ.
+pub(crate) fn count_differences(a: &[u8], b: &[u8]) -> usize {
+    differences(a, b).count()
+}
+
+/// The offsets at which `a` and `b` differ.
+///
+/// The bytes past the end of the shorter one differ from the nonexistent bytes of it.
+fn differences<'a>(a: &'a [u8], b: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    let common = a.len().min(b.len());
+    (0..common).filter(move |&offset| a[offset] != b[offset]).chain(common..a.len().max(b.len()))
+}
+
+/// Whether the byte at `offset` of `contents` differs from the one in `other`.
+pub(crate) fn differs_at(contents: &[u8], other: &[u8], offset: usize) -> bool {
+    contents.get(offset) != other.get(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal() {
+        assert_eq!(differences(b"firmware", b"firmware").collect::<Vec<_>>(), vec![]);
+        assert_eq!(differences(b"", b"").collect::<Vec<_>>(), vec![]);
+        assert_eq!(count_differences(b"firmware", b"firmware"), 0);
+        assert!(!differs_at(b"ab", b"ab", 1));
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        assert_eq!(differences(b"abcdef", b"abc").collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(differences(b"abc", b"abXde").collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(differences(b"", b"ab").collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(count_differences(b"abc", b"abXde"), 3);
+        assert!(differs_at(b"abcdef", b"abc", 4));
+        assert!(!differs_at(b"abc", b"abc", 4));
+    }
+
+    #[test]
+    fn test_scattered() {
+        let a = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+        let b = [0xFF, 0x11, 0x22, 0x30, 0x44, 0x55, 0x66, 0x70];
+        assert_eq!(differences(&a, &b).collect::<Vec<_>>(), vec![0, 3, 7]);
+        assert_eq!(differences(&b, &a).collect::<Vec<_>>(), vec![0, 3, 7]);
+        assert_eq!(count_differences(&a, &b), 3);
+        assert!(differs_at(&a, &b, 3));
+        assert!(!differs_at(&a, &b, 4));
+    }
+}
@@ -0,0 +1,172 @@
+//! Tracking which bytes were modified since the file was opened or last saved.
+
+use std::collections::BTreeMap;
+
+use crate::app::Action;
+
+/// The offsets of the bytes that differ from the file on disk.
+///
+/// Like bookmarks, the offsets move along with their bytes when bytes are inserted or removed
+/// before them. A byte that is edited back to its original value is no longer modified, and a
+/// removed byte that is restored by undoing is modified only if it was before.
+#[derive(Default)]
+pub(crate) struct DirtyBytes {
+    /// The original value of each modified byte, or `None` if it was inserted.
+    original: BTreeMap<usize, Option<u8>>,
+
+    /// The original values of the removed bytes, most recent last, which are restored along with
+    /// their bytes when the removals are undone.
+    removed: Vec<Option<u8>>,
+}
+
+impl DirtyBytes {
+    pub(crate) fn is_dirty(&self, offset: usize) -> bool {
+        self.original.contains_key(&offset)
+    }
+
+    /// Whether no byte is modified.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.original.is_empty()
+    }
+
+    /// Forgets all modifications, as the file on disk now matches the contents.
+    pub(crate) fn clear(&mut self) {
+        self.original.clear();
+        self.removed.clear();
+    }
+
+    /// Records `action`, which has just been applied to the contents.
+    pub(crate) fn edit(&mut self, action: &Action) {
+        match *action {
+            Action::CharacterInput(offset, old, new, _) => self.overwrite(offset, old, new),
+            Action::Backspace(offset, byte) | Action::Delete(offset, byte) => {
+                let original = self.remove(offset, byte);
+                self.removed.push(original);
+            }
+            Action::Insert(offset, _) => self.insert(offset, None),
+        }
+    }
+
+    /// Records that `action` has just been undone.
+    pub(crate) fn undo(&mut self, action: &Action) {
+        match *action {
+            Action::CharacterInput(offset, old, new, _) => self.overwrite(offset, new, old),
+            Action::Backspace(offset, byte) | Action::Delete(offset, byte) => {
+                // The originals of removals from before the last save are gone, so those bytes
+                // are restored as inserted ones.
+                let original = self.removed.pop().flatten();
+                if original == Some(byte) {
+                    self.shift_insert(offset);
+                } else {
+                    self.insert(offset, original);
+                }
+            }
+            Action::Insert(offset, byte) => {
+                self.remove(offset, byte);
+            }
+        }
+    }
+
+    fn overwrite(&mut self, offset: usize, old: u8, new: u8) {
+        match self.original.get(&offset) {
+            Some(&Some(original)) if original == new => {
+                self.original.remove(&offset);
+            }
+            None if old != new => {
+                self.original.insert(offset, Some(old));
+            }
+            _ => {}
+        }
+    }
+
+    /// Marks a byte inserted at `offset` as modified, with `original` as its original value.
+    fn insert(&mut self, offset: usize, original: Option<u8>) {
+        self.shift_insert(offset);
+        self.original.insert(offset, original);
+    }
+
+    fn shift_insert(&mut self, offset: usize) {
+        let moved = self.original.split_off(&offset);
+        self.original.extend(moved.into_iter().map(|(dirty, original)| (dirty + 1, original)));
+    }
+
+    /// Forgets `byte` as it was removed from `offset`, returning its original value, which is the
+    /// byte itself if it wasn't modified.
+    fn remove(&mut self, offset: usize, byte: u8) -> Option<u8> {
+        let mut moved = self.original.split_off(&offset);
+        let original = moved.remove(&offset).unwrap_or(Some(byte));
+        self.original.extend(moved.into_iter().map(|(dirty, original)| (dirty - 1, original)));
+        original
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(dirty: &DirtyBytes) -> Vec<usize> {
+        dirty.original.keys().copied().collect()
+    }
+
+    #[test]
+    fn test_marking() {
+        let mut dirty = DirtyBytes::default();
+        dirty.edit(&Action::CharacterInput(2, b'a', b'b', None));
+        dirty.edit(&Action::CharacterInput(5, b'a', b'a', None));
+        assert!(dirty.is_dirty(2));
+        assert!(!dirty.is_dirty(5));
+
+        // Modified bytes move along with inserts and deletes, and inserted bytes are modified.
+        dirty.edit(&Action::Insert(0, b'x'));
+        assert_eq!(offsets(&dirty), vec![0, 3]);
+        dirty.edit(&Action::Delete(1, b'?'));
+        assert_eq!(offsets(&dirty), vec![0, 2]);
+        dirty.edit(&Action::Backspace(2, b'b'));
+        assert_eq!(offsets(&dirty), vec![0]);
+    }
+
+    #[test]
+    fn test_reverting_to_original() {
+        let mut dirty = DirtyBytes::default();
+        dirty.edit(&Action::CharacterInput(3, b'a', b'b', None));
+        dirty.edit(&Action::CharacterInput(3, b'b', b'c', None));
+        assert!(dirty.is_dirty(3));
+        dirty.edit(&Action::CharacterInput(3, b'c', b'a', None));
+        assert!(dirty.original.is_empty());
+
+        // Undoing reverts as well.
+        dirty.edit(&Action::CharacterInput(3, b'a', b'b', None));
+        dirty.edit(&Action::Insert(1, b'x'));
+        dirty.undo(&Action::Insert(1, b'x'));
+        dirty.undo(&Action::CharacterInput(3, b'a', b'b', None));
+        assert!(dirty.original.is_empty());
+
+        // Undoing a removal restores the byte as it was, modified or not.
+        dirty.edit(&Action::Delete(2, b'c'));
+        dirty.undo(&Action::Delete(2, b'c'));
+        assert!(dirty.original.is_empty());
+        dirty.edit(&Action::CharacterInput(2, b'c', b'Z', None));
+        dirty.edit(&Action::Backspace(2, b'Z'));
+        dirty.edit(&Action::Delete(0, b'a'));
+        dirty.undo(&Action::Delete(0, b'a'));
+        dirty.undo(&Action::Backspace(2, b'Z'));
+        assert_eq!(dirty.original.iter().collect::<Vec<_>>(), vec![(&2, &Some(b'c'))]);
+        dirty.undo(&Action::CharacterInput(2, b'c', b'Z', None));
+        assert!(dirty.original.is_empty());
+
+        // An inserted byte stays modified, whatever it's edited to.
+        dirty.edit(&Action::Insert(0, b'x'));
+        dirty.edit(&Action::CharacterInput(0, b'x', b'y', None));
+        assert!(dirty.is_dirty(0));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut dirty = DirtyBytes::default();
+        dirty.edit(&Action::CharacterInput(0, b'a', b'b', None));
+        dirty.edit(&Action::Insert(4, b'x'));
+        dirty.clear();
+        assert!(dirty.original.is_empty());
+        assert!(!dirty.is_dirty(0));
+    }
+}
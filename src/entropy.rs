@@ -0,0 +1,102 @@
+//! Statistics of the byte values in the contents, which help to spot compressed or encrypted
+//! regions as their bytes are close to evenly distributed.
+
+/// The glyphs of a sparkline, from the lowest to the highest value. Nothing at all is drawn as a
+/// space.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// How often every byte value occurs in `bytes`, indexed by the value.
+pub(crate) fn byte_histogram(bytes: &[u8]) -> [u32; 256] {
+    let mut histogram = [0; 256];
+    for &byte in bytes {
+        histogram[usize::from(byte)] += 1;
+    }
+    histogram
+}
+
+/// The Shannon entropy of `bytes` in bits per byte, from 0.0 if all bytes are the same to 8.0 if
+/// all values are equally common.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn shannon_entropy(bytes: &[u8]) -> f64 {
+    let len = bytes.len() as f64;
+    byte_histogram(bytes)
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let probability = f64::from(count) / len;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+/// The entropy of each of `points` consecutive windows that `bytes` is split into, so that it can
+/// be plotted along the offsets. There are fewer points if there aren't enough bytes.
+pub(crate) fn entropy_profile(bytes: &[u8], points: usize) -> Vec<f64> {
+    if bytes.is_empty() || points == 0 {
+        return vec![];
+    }
+    let window = (bytes.len() + points - 1) / points;
+    bytes.chunks(window).map(shannon_entropy).collect()
+}
+
+/// Draws `values` as a line of bars, scaled so that `max` gets the highest one.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss, clippy::cast_sign_loss)]
+pub(crate) fn sparkline(values: &[f64], max: f64) -> String {
+    values
+        .iter()
+        .map(|&value| {
+            if value <= 0.0 || max <= 0.0 {
+                ' '
+            } else {
+                let level = (value / max * (BARS.len() - 1) as f64).round() as usize;
+                BARS[level.min(BARS.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_histogram() {
+        let histogram = byte_histogram(b"abacab\xFF");
+        assert_eq!((histogram[usize::from(b'a')], histogram[usize::from(b'b')]), (3, 2));
+        assert_eq!((histogram[usize::from(b'c')], histogram[0xFF], histogram[0]), (1, 1, 0));
+        assert_eq!(byte_histogram(&[]).iter().sum::<u32>(), 0);
+    }
+
+    #[test]
+    fn test_zero_entropy() {
+        assert!(shannon_entropy(&[0; 1024]).abs() < 1e-9);
+        assert!(shannon_entropy(&[0xAB; 3]).abs() < 1e-9);
+        assert!(shannon_entropy(&[]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_uniform_entropy() {
+        let uniform: Vec<u8> = (0..=255).cycle().take(256 * 4).collect();
+        assert!((shannon_entropy(&uniform) - 8.0).abs() < 1e-9);
+        assert!((shannon_entropy(b"abab") - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_entropy_profile() {
+        let mut bytes = vec![0; 256];
+        bytes.extend(0..=255);
+        let profile = entropy_profile(&bytes, 2);
+        assert_eq!(profile.len(), 2);
+        assert!(profile[0].abs() < 1e-9);
+        assert!((profile[1] - 8.0).abs() < 1e-9);
+
+        assert_eq!(entropy_profile(b"abc", 10).len(), 3);
+        assert!(entropy_profile(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 1.0, 4.0, 8.0], 8.0), " ▂▅█");
+        assert_eq!(sparkline(&[3.0, 3.0], 0.0), "  ");
+    }
+}
@@ -0,0 +1,162 @@
+//! Formatting bytes as literals to paste into source code.
+
+use std::fmt::{self, Display, Formatter};
+
+/// The width that exported snippets are wrapped at by default.
+pub(crate) const DEFAULT_WIDTH: usize = 80;
+
+/// The indentation of the lines that bytes are wrapped onto.
+const INDENT: &str = "    ";
+
+/// The languages that bytes can be exported as a literal of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ExportStyle {
+    /// `unsigned char data[] = { 0xDE, 0xAD };`
+    C,
+    /// `let data: &[u8] = &[0xde, 0xad];`
+    Rust,
+    /// `data = b"\xde\xad"`
+    Python,
+}
+
+impl ExportStyle {
+    pub(crate) const ALL: [ExportStyle; 3] =
+        [ExportStyle::C, ExportStyle::Rust, ExportStyle::Python];
+}
+
+impl Display for ExportStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportStyle::C => write!(f, "C array"),
+            ExportStyle::Rust => write!(f, "Rust slice"),
+            ExportStyle::Python => write!(f, "Python bytes"),
+        }
+    }
+}
+
+/// Formats `bytes` as a literal in `style`, wrapped at [`DEFAULT_WIDTH`].
+pub(crate) fn format_export(bytes: &[u8], style: ExportStyle) -> String {
+    format_export_wrapped(bytes, style, DEFAULT_WIDTH)
+}
+
+/// Formats `bytes` as a literal in `style`.
+///
+/// The snippet is kept on one line if it fits in `width` columns. Otherwise, the bytes are put on
+/// indented lines of their own, with as many bytes on each as fit. At least one byte is put on
+/// every line, however narrow `width` is.
+pub(crate) fn format_export_wrapped(bytes: &[u8], style: ExportStyle, width: usize) -> String {
+    let (open, close, items, separator) = match style {
+        ExportStyle::C => (
+            "unsigned char data[] = {",
+            "};",
+            bytes.iter().map(|byte| format!("0x{byte:02X}")).collect::<Vec<_>>(),
+            ", ",
+        ),
+        ExportStyle::Rust => (
+            "let data: &[u8] = &[",
+            "];",
+            bytes.iter().map(|byte| format!("0x{byte:02x}")).collect(),
+            ", ",
+        ),
+        ExportStyle::Python => {
+            ("data = (", ")", bytes.iter().map(|byte| format!("\\x{byte:02x}")).collect(), "")
+        }
+    };
+
+    // The literal on a single line.
+    let single = match style {
+        ExportStyle::C if bytes.is_empty() => format!("{open}{close}"),
+        ExportStyle::C => format!("{open} {} {close}", items.join(separator)),
+        ExportStyle::Rust => format!("{open}{}{close}", items.join(separator)),
+        ExportStyle::Python => format!("data = b\"{}\"", items.concat()),
+    };
+    if single.len() <= width {
+        return single;
+    }
+
+    // Each line of a Python literal is a string of its own, while the lines of an array are
+    // separated like the bytes on them.
+    let (line_start, line_end, line_separator) = match style {
+        ExportStyle::C | ExportStyle::Rust => ("", "", ","),
+        ExportStyle::Python => ("b\"", "\"", ""),
+    };
+    let fixed = INDENT.len() + line_start.len() + line_end.len() + line_separator.len();
+    let mut lines: Vec<Vec<String>> = vec![];
+    for item in items {
+        match lines.last_mut() {
+            Some(line)
+                if fixed
+                    + line.iter().map(|item| item.len() + separator.len()).sum::<usize>()
+                    + item.len()
+                    <= width =>
+            {
+                line.push(item);
+            }
+            _ => lines.push(vec![item]),
+        }
+    }
+    let lines = lines
+        .iter()
+        .map(|line| format!("{INDENT}{line_start}{}{line_end}", line.join(separator)))
+        .collect::<Vec<_>>();
+    format!("{open}\n{}\n{close}", lines.join(&format!("{line_separator}\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c() {
+        assert_eq!(
+            format_export(&[0xDE, 0xAD], ExportStyle::C),
+            "unsigned char data[] = { 0xDE, 0xAD };"
+        );
+        assert_eq!(format_export(&[], ExportStyle::C), "unsigned char data[] = {};");
+    }
+
+    #[test]
+    fn test_rust() {
+        assert_eq!(
+            format_export(&[0xDE, 0xAD], ExportStyle::Rust),
+            "let data: &[u8] = &[0xde, 0xad];"
+        );
+        assert_eq!(format_export(&[], ExportStyle::Rust), "let data: &[u8] = &[];");
+    }
+
+    #[test]
+    fn test_python() {
+        assert_eq!(format_export(&[0xDE, 0xAD], ExportStyle::Python), "data = b\"\\xde\\xad\"");
+        assert_eq!(format_export(&[], ExportStyle::Python), "data = b\"\"");
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let bytes = [0x00, 0x01, 0x02, 0x03, 0x04];
+        assert_eq!(
+            format_export_wrapped(&bytes, ExportStyle::C, 21),
+            "unsigned char data[] = {\n    0x00, 0x01, 0x02,\n    0x03, 0x04\n};"
+        );
+        assert_eq!(
+            format_export_wrapped(&bytes, ExportStyle::Rust, 21),
+            "let data: &[u8] = &[\n    0x00, 0x01, 0x02,\n    0x03, 0x04\n];"
+        );
+        assert_eq!(
+            format_export_wrapped(&bytes, ExportStyle::Python, 19),
+            "data = (\n    b\"\\x00\\x01\\x02\"\n    b\"\\x03\\x04\"\n)"
+        );
+
+        // Every line fits in the width.
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        for style in ExportStyle::ALL {
+            let export = format_export_wrapped(&bytes, style, 40);
+            assert!(export.lines().all(|line| line.len() <= 40), "{export}");
+        }
+
+        // A byte is put on every line even if it doesn't fit.
+        assert_eq!(
+            format_export_wrapped(&[0xAB, 0xCD], ExportStyle::C, 1),
+            "unsigned char data[] = {\n    0xAB,\n    0xCD\n};"
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! Overwriting a range of bytes with a repeating pattern.
+
+use std::ops::Range;
+
+/// Overwrites `range` of `buf` with `pattern` over and over, cutting the last repeat short so that
+/// nothing after the range is changed.
+///
+/// Fails without changing anything if the pattern is empty.
+pub(crate) fn fill_range(
+    buf: &mut [u8],
+    range: Range<usize>,
+    pattern: &[u8],
+) -> Result<(), String> {
+    if pattern.is_empty() {
+        return Err(String::from("Nothing to fill with!"));
+    }
+    for (byte, &new) in buf[range].iter_mut().zip(pattern.iter().cycle()) {
+        *byte = new;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_byte() {
+        let mut buf = b"abcdef".to_vec();
+        fill_range(&mut buf, 1..4, &[0xFF]).unwrap();
+        assert_eq!(buf, b"a\xFF\xFF\xFFef");
+        fill_range(&mut buf, 0..6, &[0]).unwrap();
+        assert_eq!(buf, [0; 6]);
+    }
+
+    #[test]
+    fn test_pattern_with_partial_tail() {
+        let mut buf = b"abcdefgh".to_vec();
+        fill_range(&mut buf, 1..8, &[0xDE, 0xAD, 0xBE]).unwrap();
+        assert_eq!(buf, b"a\xDE\xAD\xBE\xDE\xAD\xBE\xDE");
+
+        // A pattern longer than the range is cut short as well.
+        let mut buf = b"abcd".to_vec();
+        fill_range(&mut buf, 0..2, b"XYZ").unwrap();
+        assert_eq!(buf, b"XYcd");
+    }
+
+    #[test]
+    fn test_empty_pattern() {
+        let mut buf = b"abcd".to_vec();
+        assert!(fill_range(&mut buf, 0..4, &[]).is_err());
+        assert_eq!(buf, b"abcd");
+    }
+}
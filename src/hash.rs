@@ -0,0 +1,109 @@
+//! Checksums of the file contents, for verifying them against known ones.
+//!
+//! CRC32 is always available, while MD5 and SHA-256 need the `hashes` feature.
+
+#[cfg(feature = "hashes")]
+use std::fmt::Write;
+
+#[cfg(feature = "hashes")]
+use md5::Md5;
+#[cfg(feature = "hashes")]
+use sha2::{Digest, Sha256};
+
+/// The checksums of some bytes, as lowercase hexadecimal.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct HashSummary {
+    pub(crate) crc32: String,
+    #[cfg(feature = "hashes")]
+    pub(crate) md5: String,
+    #[cfg(feature = "hashes")]
+    pub(crate) sha256: String,
+}
+
+impl HashSummary {
+    /// The name and value of every checksum.
+    pub(crate) fn entries(&self) -> Vec<(&'static str, &str)> {
+        #[cfg_attr(not(feature = "hashes"), allow(unused_mut))]
+        let mut entries = vec![("CRC32", self.crc32.as_str())];
+        #[cfg(feature = "hashes")]
+        entries.extend([("MD5", self.md5.as_str()), ("SHA-256", self.sha256.as_str())]);
+        entries
+    }
+}
+
+/// Computes all checksums of `bytes`.
+pub(crate) fn hashes(bytes: &[u8]) -> HashSummary {
+    HashSummary {
+        crc32: format!("{:08x}", crc32(bytes)),
+        #[cfg(feature = "hashes")]
+        md5: to_hex(&Md5::digest(bytes)),
+        #[cfg(feature = "hashes")]
+        sha256: to_hex(&Sha256::digest(bytes)),
+    }
+}
+
+#[cfg(feature = "hashes")]
+fn to_hex(digest: &[u8]) -> String {
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        // Writing to a string can't fail.
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+/// The CRC-32 (as used by zlib and PNG) of `bytes`.
+fn crc32(bytes: &[u8]) -> u32 {
+    // The reversed polynomial, as the bits are processed from least to most significant.
+    const POLYNOMIAL: u32 = 0xEDB8_8320;
+    let mut crc = !0;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ POLYNOMIAL } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(hashes(b"").crc32, "00000000");
+        assert_eq!(hashes(b"abc").crc32, "352441c2");
+        assert_eq!(hashes(b"123456789").crc32, "cbf43926");
+    }
+
+    #[cfg(feature = "hashes")]
+    #[test]
+    fn test_md5() {
+        assert_eq!(hashes(b"").md5, "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(hashes(b"abc").md5, "900150983cd24fb0d6963f7d28e17f72");
+    }
+
+    #[cfg(feature = "hashes")]
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            hashes(b"").sha256,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hashes(b"abc").sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_entries() {
+        let names: Vec<_> = hashes(b"abc").entries().into_iter().map(|(name, _)| name).collect();
+        if cfg!(feature = "hashes") {
+            assert_eq!(names, ["CRC32", "MD5", "SHA-256"]);
+        } else {
+            assert_eq!(names, ["CRC32"]);
+        }
+    }
+}
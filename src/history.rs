@@ -0,0 +1,306 @@
+//! The undo and redo stack of the edits made to the file.
+
+use std::collections::VecDeque;
+
+use crate::{
+    app::{Action, Nibble},
+    buffer::Contents,
+};
+
+/// The number of groups of edits that can be undone by default.
+const DEFAULT_LIMIT: usize = 1000;
+
+/// The contents that edits are undone and redone on.
+///
+/// [`AppData`](crate::app::AppData) moves its cursor, selections, and bookmarks along with the
+/// bytes that are inserted and removed, which editing the bytes alone wouldn't.
+pub(crate) trait Replay {
+    /// Overwrites the byte at `offset` with `byte`.
+    fn set(&mut self, offset: usize, byte: u8);
+    /// Inserts `byte` at `offset`.
+    fn insert(&mut self, offset: usize, byte: u8);
+    /// Removes the byte at `offset`.
+    fn remove(&mut self, offset: usize);
+}
+
+impl Replay for Contents {
+    fn set(&mut self, offset: usize, byte: u8) {
+        self[offset] = byte;
+    }
+    fn insert(&mut self, offset: usize, byte: u8) {
+        Contents::insert(self, offset, byte);
+    }
+    fn remove(&mut self, offset: usize) {
+        Contents::remove(self, offset);
+    }
+}
+
+/// A bounded history of [`Action`]s that can be undone and redone.
+///
+/// Consecutive character inputs at the same or adjacent offsets are coalesced into one group, so
+/// that a typed run is undone at once. Making an edit after undoing discards the undone groups.
+pub(crate) struct EditHistory {
+    /// The groups of actions, oldest first.
+    groups: VecDeque<Vec<Action>>,
+
+    /// The number of groups that are currently applied, the ones after it can be redone.
+    applied: usize,
+
+    /// The maximum number of groups that are kept.
+    limit: usize,
+
+    /// Whether the most recent group was pushed as a whole, so nothing is coalesced into it.
+    sealed: bool,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::with_limit(DEFAULT_LIMIT)
+    }
+}
+
+impl EditHistory {
+    /// Creates a history that keeps at most `limit` groups of edits, dropping the oldest ones.
+    pub(crate) fn with_limit(limit: usize) -> Self {
+        Self { groups: VecDeque::new(), applied: 0, limit, sealed: false }
+    }
+
+    /// Records an action that has just been applied to the contents.
+    pub(crate) fn push(&mut self, action: Action) {
+        // Edits made after undoing always start a new group.
+        let branching = self.applied < self.groups.len();
+        self.groups.truncate(self.applied);
+
+        let coalesce = !branching
+            && !self.sealed
+            && match (self.groups.back().and_then(|group| group.last()), &action) {
+                (
+                    Some(Action::CharacterInput(previous, ..) | Action::Insert(previous, _)),
+                    Action::CharacterInput(offset, ..) | Action::Insert(offset, _),
+                ) => *offset == *previous || *offset == previous + 1,
+                _ => false,
+            };
+        if let (true, Some(group)) = (coalesce, self.groups.back_mut()) {
+            group.push(action);
+            self.applied = self.groups.len();
+        } else {
+            self.push_new(vec![action]);
+        }
+        self.sealed = false;
+    }
+
+    /// Records actions that have just been applied to the contents as one group, which is undone
+    /// at once and never coalesced with the actions before or after it.
+    pub(crate) fn push_group(&mut self, group: Vec<Action>) {
+        if group.is_empty() {
+            return;
+        }
+        self.groups.truncate(self.applied);
+        self.push_new(group);
+        self.sealed = true;
+    }
+
+    fn push_new(&mut self, group: Vec<Action>) {
+        self.groups.push_back(group);
+        if self.groups.len() > self.limit {
+            self.groups.pop_front();
+        }
+        self.applied = self.groups.len();
+    }
+
+    /// The group of edits that [`undo`](Self::undo) reverts next.
+    pub(crate) fn next_undo(&self) -> Option<&[Action]> {
+        let group = self.applied.checked_sub(1)?;
+        Some(&self.groups[group])
+    }
+
+    /// The group of edits that [`redo`](Self::redo) reapplies next.
+    pub(crate) fn next_redo(&self) -> Option<&[Action]> {
+        self.groups.get(self.applied).map(Vec::as_slice)
+    }
+
+    /// Reverts the most recently applied group of edits on `contents`, returning the offset (and
+    /// nibble) the cursor should move to.
+    pub(crate) fn undo(&mut self, contents: &mut impl Replay) -> Option<(usize, Option<Nibble>)> {
+        self.applied = self.applied.checked_sub(1)?;
+        let mut cursor = None;
+        for action in self.groups[self.applied].iter().rev() {
+            cursor = Some(match *action {
+                Action::CharacterInput(offset, old, _, nibble) => {
+                    contents.set(offset, old);
+                    (offset, nibble)
+                }
+                Action::Backspace(offset, byte) => {
+                    contents.insert(offset, byte);
+                    (offset + 1, None)
+                }
+                Action::Delete(offset, byte) => {
+                    contents.insert(offset, byte);
+                    (offset, None)
+                }
+                Action::Insert(offset, _) => {
+                    contents.remove(offset);
+                    (offset, None)
+                }
+            });
+        }
+        cursor
+    }
+
+    /// Reapplies the most recently undone group of edits on `contents`, returning the offset (and
+    /// nibble) the cursor should move to.
+    pub(crate) fn redo(&mut self, contents: &mut impl Replay) -> Option<(usize, Option<Nibble>)> {
+        let group = self.groups.get(self.applied)?;
+        let mut cursor = None;
+        for action in group {
+            cursor = Some(match *action {
+                Action::CharacterInput(offset, _, new, nibble) => {
+                    contents.set(offset, new);
+                    (offset, nibble)
+                }
+                Action::Backspace(offset, _) | Action::Delete(offset, _) => {
+                    contents.remove(offset);
+                    (offset, None)
+                }
+                Action::Insert(offset, byte) => {
+                    contents.insert(offset, byte);
+                    (offset + 1, None)
+                }
+            });
+        }
+        self.applied += 1;
+        cursor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Overwrites the byte at `offset` and records it in `history`.
+    fn overwrite(history: &mut EditHistory, contents: &mut [u8], offset: usize, new: u8) {
+        history.push(Action::CharacterInput(offset, contents[offset], new, None));
+        contents[offset] = new;
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = EditHistory::default();
+        let mut contents = Contents::from(b"abcdef".to_vec());
+
+        overwrite(&mut history, &mut contents, 4, b'X');
+        history.push(Action::Delete(0, contents.remove(0)));
+        assert_eq!(contents, b"bcdXf");
+
+        assert_eq!(history.undo(&mut contents), Some((0, None)));
+        assert_eq!(contents, b"abcdXf");
+        assert_eq!(history.undo(&mut contents), Some((4, None)));
+        assert_eq!(contents, b"abcdef");
+        assert_eq!(history.undo(&mut contents), None);
+
+        assert_eq!(history.redo(&mut contents), Some((4, None)));
+        assert_eq!(contents, b"abcdXf");
+        assert_eq!(history.redo(&mut contents), Some((0, None)));
+        assert_eq!(contents, b"bcdXf");
+        assert_eq!(history.redo(&mut contents), None);
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let mut history = EditHistory::default();
+        let mut contents = Contents::from(b"abcdef".to_vec());
+
+        // Typing a run, then continuing somewhere else.
+        overwrite(&mut history, &mut contents, 1, b'X');
+        overwrite(&mut history, &mut contents, 2, b'Y');
+        overwrite(&mut history, &mut contents, 3, b'Z');
+        overwrite(&mut history, &mut contents, 5, b'!');
+        assert_eq!(contents, b"aXYZe!");
+
+        assert_eq!(history.undo(&mut contents), Some((5, None)));
+        assert_eq!(contents, b"aXYZef");
+        assert_eq!(history.undo(&mut contents), Some((1, None)));
+        assert_eq!(contents, b"abcdef");
+        assert_eq!(history.redo(&mut contents), Some((3, None)));
+        assert_eq!(contents, b"aXYZef");
+
+        // Both nibbles of a byte are edited at the same offset.
+        let mut history = EditHistory::default();
+        let mut contents = Contents::from(vec![0x00]);
+        history.push(Action::CharacterInput(0, 0x00, 0xA0, Some(Nibble::Beginning)));
+        history.push(Action::CharacterInput(0, 0xA0, 0xAB, Some(Nibble::End)));
+        contents[0] = 0xAB;
+        assert_eq!(history.undo(&mut contents), Some((0, Some(Nibble::Beginning))));
+        assert_eq!(contents, [0x00]);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut history = EditHistory::default();
+        let mut contents = Contents::from(b"ad".to_vec());
+
+        // Typing a run in insert mode is undone at once.
+        contents.insert(1, b'b');
+        history.push(Action::Insert(1, b'b'));
+        contents.insert(2, b'c');
+        history.push(Action::Insert(2, b'c'));
+        assert_eq!(contents, b"abcd");
+
+        assert_eq!(history.undo(&mut contents), Some((1, None)));
+        assert_eq!(contents, b"ad");
+        assert_eq!(history.redo(&mut contents), Some((3, None)));
+        assert_eq!(contents, b"abcd");
+    }
+
+    #[test]
+    fn test_push_group() {
+        let mut history = EditHistory::default();
+        let mut contents = Contents::from(b"abcdef".to_vec());
+
+        overwrite(&mut history, &mut contents, 0, b'X');
+        let group = (1..4).map(|offset| Action::CharacterInput(offset, contents[offset], 0, None));
+        history.push_group(group.collect());
+        contents[1..4].fill(0);
+        overwrite(&mut history, &mut contents, 4, b'Y');
+        assert_eq!(contents, b"X\0\0\0Yf");
+
+        // Neither the input before nor the one after the group is coalesced into it.
+        assert_eq!(history.undo(&mut contents), Some((4, None)));
+        assert_eq!(contents, b"X\0\0\0ef");
+        assert_eq!(history.undo(&mut contents), Some((1, None)));
+        assert_eq!(contents, b"Xbcdef");
+        assert_eq!(history.undo(&mut contents), Some((0, None)));
+        assert_eq!(contents, b"abcdef");
+    }
+
+    #[test]
+    fn test_branch_truncation() {
+        let mut history = EditHistory::default();
+        let mut contents = Contents::from(b"abc".to_vec());
+
+        overwrite(&mut history, &mut contents, 0, b'X');
+        history.undo(&mut contents);
+        overwrite(&mut history, &mut contents, 2, b'Z');
+        assert_eq!(contents, b"abZ");
+
+        // The undone edit is gone for good.
+        assert_eq!(history.redo(&mut contents), None);
+        assert_eq!(history.undo(&mut contents), Some((2, None)));
+        assert_eq!(history.undo(&mut contents), None);
+        assert_eq!(contents, b"abc");
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut history = EditHistory::with_limit(2);
+        let mut contents = Contents::from(b"abcdef".to_vec());
+
+        overwrite(&mut history, &mut contents, 0, b'X');
+        overwrite(&mut history, &mut contents, 2, b'Y');
+        overwrite(&mut history, &mut contents, 4, b'Z');
+        assert!(history.undo(&mut contents).is_some());
+        assert!(history.undo(&mut contents).is_some());
+        assert!(history.undo(&mut contents).is_none());
+        assert_eq!(contents, b"Xbcdef");
+    }
+}
@@ -3,39 +3,64 @@
 //! This is where mouse actions are programmed. It's also a wrapper around calls to a dynamic
 //! [`KeyHandler`](crate::windows::KeyHandler), which handles keyboared input.
 
-use std::{
-    cmp,
-    error::Error,
-    io::{Seek, SeekFrom, Write},
-};
+use std::cmp;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
 use crate::{
-    app::{Action, Application, Nibble},
+    app::{Application, Nibble},
+    clipboard::Clipboard,
+    decoder::{decode_text, Endian},
     label::LABEL_TITLES,
-    windows::{adjust_offset, PopupOutput, Window},
+    paste::{parse_paste, PasteMode},
+    screen::BYTES_PER_ROW_PRESETS,
+    windows::{
+        adjust_offset,
+        bookmarks::jump_to_bookmark,
+        copy_to_clipboard, editor,
+        search::{jump_to_match, jump_to_nonprintable},
+        PopupOutput, Window,
+    },
 };
 
 /// Wrapper function that calls the corresponding [`KeyHandler`](crate::windows::KeyHandler) methods of
 /// [the application's `key_handler`.](Application::key_handler)
-pub(crate) fn handle_key_input(
-    app: &mut Application,
-    key: KeyEvent,
-) -> Result<bool, Box<dyn Error>> {
+///
+/// Returns `false` if the application should quit.
+pub(crate) fn handle_key_input(app: &mut Application, key: KeyEvent) -> bool {
     match key.code {
-        // Arrow key input
-        KeyCode::Left => {
-            app.key_handler.left(&mut app.data, &mut app.display, &mut app.labels);
-        }
-        KeyCode::Right => {
-            app.key_handler.right(&mut app.data, &mut app.display, &mut app.labels);
-        }
-        KeyCode::Up => {
-            app.key_handler.up(&mut app.data, &mut app.display, &mut app.labels);
+        // Arrow key input, which extends the selection while shift is held
+        KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down => {
+            let anchor = (key.modifiers == KeyModifiers::SHIFT).then(|| {
+                let selection = app.data.selection();
+                (selection.anchor, app.data.drag_nibble.unwrap_or(app.data.nibble))
+            });
+            match key.code {
+                KeyCode::Left => {
+                    app.key_handler.left(&mut app.data, &mut app.display, &mut app.labels);
+                }
+                KeyCode::Right => {
+                    app.key_handler.right(&mut app.data, &mut app.display, &mut app.labels);
+                }
+                KeyCode::Up => {
+                    app.key_handler.up(&mut app.data, &mut app.display, &mut app.labels);
+                }
+                _ => {
+                    app.key_handler.down(&mut app.data, &mut app.display, &mut app.labels);
+                }
+            }
+            if let Some((anchor, nibble)) = anchor {
+                app.data.last_drag = Some(anchor);
+                app.data.drag_nibble = Some(nibble);
+            }
         }
-        KeyCode::Down => {
-            app.key_handler.down(&mut app.data, &mut app.display, &mut app.labels);
+
+        // Tabs, which are switched with CNTRLPAGEDOWN and CNTRLPAGEUP as well, as most terminals
+        // report CNTRLTAB as a plain TAB
+        KeyCode::Tab | KeyCode::BackTab | KeyCode::PageDown | KeyCode::PageUp
+            if key.modifiers.contains(KeyModifiers::CONTROL) && is_editing(app) =>
+        {
+            app.switch_tab(matches!(key.code, KeyCode::Tab | KeyCode::PageDown));
         }
 
         // Cursor shortcuts
@@ -62,17 +87,42 @@ pub(crate) fn handle_key_input(
         KeyCode::Esc => {
             app.focus_editor();
         }
+        KeyCode::Insert => {
+            app.data.insert_mode = !app.data.insert_mode;
+        }
 
         KeyCode::Enter => {
             if app.key_handler.is_focusing(Window::UnsavedChanges)
                 && app.key_handler.get_user_input() == PopupOutput::Boolean(true)
             {
-                return Ok(false);
+                return false;
+            }
+            // Opening and closing files changes the tabs, which the windows have no access to.
+            if app.key_handler.is_focusing(Window::CloseTab) {
+                let close = app.key_handler.get_user_input() == PopupOutput::Boolean(true);
+                app.focus_editor();
+                if close {
+                    app.close_tab();
+                }
+                return true;
+            }
+            if let PopupOutput::Str(path) = app.key_handler.get_user_input() {
+                if app.key_handler.is_focusing(Window::OpenFile) {
+                    let path = path.to_owned();
+                    app.focus_editor();
+                    app.open_tab(&path);
+                    return true;
+                }
             }
             app.key_handler.enter(&mut app.data, &mut app.display, &mut app.labels);
             app.focus_editor();
         }
 
+        // Reloading replaces the contents of the active tab, which the windows have no access to.
+        KeyCode::Char('r' | 'R') if app.key_handler.is_focusing(Window::Reload) => {
+            app.focus_editor();
+            app.reload();
+        }
         KeyCode::Char(char) => {
             // Because CNTRLq is the signal to quit, we propogate the message
             // if this handling method returns false
@@ -80,80 +130,68 @@ pub(crate) fn handle_key_input(
         }
         _ => {}
     }
-    Ok(true)
+    true
+}
+
+/// Whether one of the editors is focused rather than a popup.
+fn is_editing(app: &Application) -> bool {
+    app.key_handler.is_focusing(Window::Hex) || app.key_handler.is_focusing(Window::Ascii)
+}
+
+/// Opens the popup `window`, or closes it if it is open already.
+fn toggle_window(app: &mut Application, window: Window) {
+    if app.key_handler.is_focusing(window) {
+        app.focus_editor();
+    } else {
+        app.set_focused_window(window);
+    }
+}
+
+/// Pastes the text on the clipboard into the focused editor.
+fn paste_clipboard(app: &mut Application, mode: PasteMode) {
+    if !is_editing(app) {
+        return;
+    }
+    let text = match app.data.clipboard.as_mut().map(Clipboard::get_text) {
+        Some(Ok(text)) => text,
+        Some(Err(_)) => {
+            app.labels.notification = String::from("Can't read the clipboard!");
+            return;
+        }
+        None => {
+            app.labels.notification = String::from("Can't find clipboard!");
+            return;
+        }
+    };
+    match parse_paste(&text, mode) {
+        Ok(bytes) => editor::paste(&mut app.data, &mut app.display, &mut app.labels, &bytes),
+        Err(error) => app.labels.notification = error.to_string(),
+    }
+}
+
+/// Moves the cursor to where a change was undone or redone.
+fn move_cursor_after_history(app: &mut Application, offset: usize, nibble: Option<Nibble>) {
+    app.data.offset = cmp::min(offset, app.data.contents.len() - 1);
+    if let Some(nibble) = nibble {
+        app.data.nibble = nibble;
+    }
+    app.labels.update_all(&app.data.contents[app.data.offset..], app.data.endian);
+    adjust_offset(&mut app.data, &mut app.display, &mut app.labels);
 }
 
 /// Handles a character key press. While used predominantly to edit a file, it also checks for
 /// any shortcut commands being used.
+///
+/// Returns `false` if the application should quit.
 pub(crate) fn handle_character_input(
     app: &mut Application,
     char: char,
     modifiers: KeyModifiers,
-) -> Result<bool, Box<dyn Error>> {
+) -> bool {
     if modifiers == KeyModifiers::CONTROL {
-        match char {
-            'j' => {
-                if app.key_handler.is_focusing(Window::JumpToByte) {
-                    app.focus_editor();
-                } else {
-                    app.set_focused_window(Window::JumpToByte);
-                }
-            }
-            'q' => {
-                if !app.key_handler.is_focusing(Window::UnsavedChanges) {
-                    if app.hash_contents() == app.data.hashed_contents {
-                        return Ok(false);
-                    }
-                    app.set_focused_window(Window::UnsavedChanges);
-                }
-            }
-            's' => {
-                app.data.file.seek(SeekFrom::Start(0))?;
-                app.data.file.write_all(&app.data.contents)?;
-                app.data.file.set_len(app.data.contents.len() as u64)?;
-
-                app.data.hashed_contents = app.hash_contents();
-
-                app.labels.notification = String::from("Saved!");
-            }
-            'z' => {
-                if let Some(action) = app.data.actions.pop() {
-                    match action {
-                        Action::CharacterInput(offset, byte, nibble) => {
-                            app.data.offset = offset;
-                            if let Some(nibble) = nibble {
-                                app.data.nibble = nibble;
-                            }
-                            app.data.contents[offset] = byte;
-                        }
-                        Action::Backspace(offset, byte) => {
-                            app.data.contents.insert(offset, byte);
-                            app.data.offset = offset + 1;
-                        }
-                        Action::Delete(offset, byte) => {
-                            app.data.contents.insert(offset, byte);
-                            app.data.offset = offset;
-                        }
-                    }
-                }
-            }
-            _ => {}
-        }
+        return handle_control_input(app, char);
     } else if modifiers == KeyModifiers::ALT {
-        match char {
-            '=' => {
-                app.labels.update_stream_length(cmp::min(app.labels.get_stream_length() + 1, 64));
-                app.labels.update_streams(&app.data.contents[app.data.offset..]);
-            }
-            '-' => {
-                app.labels.update_stream_length(cmp::max(
-                    app.labels.get_stream_length().saturating_sub(1),
-                    0,
-                ));
-                app.labels.update_streams(&app.data.contents[app.data.offset..]);
-            }
-            _ => {}
-        }
+        handle_alt_input(app, char);
     } else if modifiers | KeyModifiers::NONE | KeyModifiers::SHIFT
         == KeyModifiers::NONE | KeyModifiers::SHIFT
     {
@@ -162,12 +200,15 @@ pub(crate) fn handle_character_input(
         match char {
             'q' if is_hex => {
                 if !app.key_handler.is_focusing(Window::UnsavedChanges) {
-                    if app.hash_contents() == app.data.hashed_contents {
-                        return Ok(false);
+                    if !app.has_unsaved_changes() {
+                        return false;
                     }
                     app.set_focused_window(Window::UnsavedChanges);
                 }
             }
+            'g' | ':' if is_hex => {
+                app.set_focused_window(Window::JumpToByte);
+            }
             'h' if is_hex => {
                 app.key_handler.left(&mut app.data, &mut app.display, &mut app.labels);
             }
@@ -191,7 +232,167 @@ pub(crate) fn handle_character_input(
             }
         }
     }
-    Ok(true)
+    true
+}
+
+/// Handles the shortcut commands of CNTRL and `char`, returning `false` if the application should
+/// quit.
+fn handle_control_input(app: &mut Application, char: char) -> bool {
+    match char {
+        'e' => {
+            app.data.encoding = app.data.encoding.next();
+            app.labels.notification = format!("Encoding: {}", app.data.encoding);
+        }
+        'j' => toggle_window(app, Window::JumpToByte),
+        'f' => toggle_window(app, Window::Search),
+        'b' => {
+            if app.key_handler.is_focusing(Window::BookmarkLabel) {
+                app.focus_editor();
+            } else if app.data.bookmarks.contains(app.data.offset) {
+                app.data.bookmarks.toggle(app.data.offset, None);
+                app.labels.notification = format!("Removed bookmark {:#X}!", app.data.offset);
+            } else {
+                app.set_focused_window(Window::BookmarkLabel);
+            }
+        }
+        'l' => {
+            if app.key_handler.is_focusing(Window::BookmarkList) {
+                app.focus_editor();
+            } else if app.data.bookmarks.is_empty() {
+                app.labels.notification = String::from("No bookmarks!");
+            } else {
+                app.set_focused_window(Window::BookmarkList);
+            }
+        }
+        'n' => {
+            let start = app.data.offset + 1;
+            jump_to_match(&mut app.data, &mut app.display, &mut app.labels, start, true);
+        }
+        'p' => {
+            let start = app.data.offset;
+            jump_to_match(&mut app.data, &mut app.display, &mut app.labels, start, false);
+        }
+        'c' => {
+            let selected = &app.data.contents[app.data.selection().range()];
+            let hex = selected.iter().map(|byte| format!("{byte:02X}")).collect::<Vec<_>>();
+            copy_to_clipboard(&mut app.data, &mut app.labels, hex.join(" "), "Hex");
+        }
+        'v' => paste_clipboard(app, PasteMode::Detect(app.data.encoding)),
+        'x' => toggle_window(app, Window::Export),
+        'o' => toggle_window(app, Window::OpenFile),
+        'w' => {
+            if app.tabs.len() == 1 {
+                app.labels.notification = String::from("Can't close the only file!");
+            } else if app.data.has_unsaved_changes() {
+                app.set_focused_window(Window::CloseTab);
+            } else {
+                app.close_tab();
+            }
+        }
+        'q' if !app.key_handler.is_focusing(Window::UnsavedChanges) => {
+            if !app.has_unsaved_changes() {
+                return false;
+            }
+            app.set_focused_window(Window::UnsavedChanges);
+        }
+        's' => {
+            if let Err(error) = app.data.save() {
+                app.labels.notification = format!("Can't save: {error}");
+                return true;
+            }
+            app.labels.notification = String::from("Saved!");
+        }
+        'a' => toggle_window(app, Window::SaveAs),
+        'z' => match app.data.undo() {
+            Ok(Some((offset, nibble))) => move_cursor_after_history(app, offset, nibble),
+            Ok(None) => {}
+            Err(error) => app.labels.notification = error.to_string(),
+        },
+        'y' => match app.data.redo() {
+            Ok(Some((offset, nibble))) => move_cursor_after_history(app, offset, nibble),
+            Ok(None) => {}
+            Err(error) => app.labels.notification = error.to_string(),
+        },
+        'r' => {
+            app.labels.notification = match app.data.toggle_read_only() {
+                Ok(()) if app.data.read_only => String::from("Read-only!"),
+                Ok(()) => String::from("Editable!"),
+                Err(error) => error.to_string(),
+            };
+        }
+        _ => {}
+    }
+    true
+}
+
+/// Handles the shortcut commands of ALT and `char`.
+fn handle_alt_input(app: &mut Application, char: char) {
+    match char {
+        '=' => {
+            app.labels.update_stream_length(cmp::min(app.labels.get_stream_length() + 1, 64));
+            app.labels.update_streams(&app.data.contents[app.data.offset..]);
+        }
+        '-' => {
+            app.labels.update_stream_length(cmp::max(
+                app.labels.get_stream_length().saturating_sub(1),
+                0,
+            ));
+            app.labels.update_streams(&app.data.contents[app.data.offset..]);
+        }
+        'b' => {
+            app.data.endian = match app.data.endian {
+                Endian::Little => Endian::Big,
+                Endian::Big => Endian::Little,
+            };
+            app.labels.update_all(&app.data.contents[app.data.offset..], app.data.endian);
+        }
+        'e' => {
+            app.data.encoding = app.data.encoding.prev();
+            app.labels.notification = format!("Encoding: {}", app.data.encoding);
+        }
+        'c' => {
+            // The clipboard only holds text, so the bytes are copied as the text they decode to,
+            // which can't be done if any of them is undecodable.
+            let selected = &app.data.contents[app.data.selection().range()];
+            if let Some(text) = decode_text(selected, app.data.encoding) {
+                copy_to_clipboard(&mut app.data, &mut app.labels, text, "Bytes");
+            } else {
+                app.labels.notification = format!("Not {} text!", app.data.encoding);
+            }
+        }
+        'v' => paste_clipboard(app, PasteMode::Text(app.data.encoding)),
+        's' => toggle_window(app, Window::Strings),
+        'f' => toggle_window(app, Window::Fill),
+        'h' => toggle_window(app, Window::Checksums),
+        't' => toggle_window(app, Window::Entropy),
+        'r' => {
+            // Cycles from fitting as many bytes as possible through the presets.
+            let next = match app.display.bytes_per_row {
+                None => Some(BYTES_PER_ROW_PRESETS[0]),
+                Some(current) => {
+                    BYTES_PER_ROW_PRESETS.iter().copied().find(|&preset| preset > current)
+                }
+            };
+            app.set_bytes_per_row(next);
+            app.labels.notification = match next {
+                Some(_) => {
+                    format!("Bytes per row: {}", app.display.comp_layouts.bytes_per_line)
+                }
+                None => String::from("Bytes per row: auto"),
+            };
+        }
+        'a' => {
+            let base = app.display.address_base.next();
+            app.display.set_address_base(&mut app.data, app.key_handler.as_ref(), base);
+            adjust_offset(&mut app.data, &mut app.display, &mut app.labels);
+            app.labels.notification = format!("{base} addresses");
+        }
+        'n' => jump_to_bookmark(&mut app.data, &mut app.display, &mut app.labels, true),
+        'p' => jump_to_bookmark(&mut app.data, &mut app.display, &mut app.labels, false),
+        'j' => jump_to_nonprintable(&mut app.data, &mut app.display, &mut app.labels, true),
+        'k' => jump_to_nonprintable(&mut app.data, &mut app.display, &mut app.labels, false),
+        _ => {}
+    }
 }
 
 /// Handles the mouse input, which consists of things like scrolling and focusing components
@@ -217,62 +418,50 @@ pub(crate) fn handle_mouse_input(app: &mut Application, mouse: MouseEvent) {
                 }
                 Window::Label(_)
                 | Window::Unhandled
+                | Window::BookmarkLabel
+                | Window::BookmarkList
+                | Window::Checksums
+                | Window::CloseTab
+                | Window::Entropy
+                | Window::Export
+                | Window::Fill
                 | Window::JumpToByte
+                | Window::OpenFile
+                | Window::SaveAs
+                | Window::Search
+                | Window::Strings
+                | Window::Reload
                 | Window::UnsavedChanges => {}
             }
         }
-        MouseEventKind::Drag(MouseButton::Left) => {
-            if app.data.drag_enabled {
-                match app.data.last_click {
-                    Window::Ascii => {
-                        if let Some((cursor_pos, _)) = handle_editor_drag(Window::Ascii, app, mouse)
-                        {
-                            if app.data.last_drag.is_none() {
-                                app.data.last_drag = Some(app.data.offset);
-                            }
-                            app.data.offset = cursor_pos;
-                            app.labels.update_all(&app.data.contents[app.data.offset..]);
-                            adjust_offset(&mut app.data, &mut app.display, &mut app.labels);
-                        }
-                    }
-                    Window::Hex => {
-                        if let Some((cursor_pos, nibble)) =
-                            handle_editor_drag(Window::Hex, app, mouse)
-                        {
-                            if app.data.last_drag.is_none() {
-                                app.data.last_drag = Some(app.data.offset);
-                                app.data.drag_nibble = Some(app.data.nibble);
-                            }
-                            app.data.offset = cursor_pos;
-                            app.data.nibble = nibble.unwrap();
-                            app.labels.update_all(&app.data.contents[app.data.offset..]);
-                            adjust_offset(&mut app.data, &mut app.display, &mut app.labels);
-                        }
-                    }
-                    Window::Label(_)
-                    | Window::Unhandled
-                    | Window::JumpToByte
-                    | Window::UnsavedChanges => {}
-                }
-            }
+        MouseEventKind::Drag(MouseButton::Left) if app.data.drag_enabled => {
+            handle_mouse_drag(app, mouse);
         }
         MouseEventKind::Up(MouseButton::Left) => {
             match component {
                 Window::Label(i) => {
                     if app.data.last_click == component {
                         // Put string into clipboard
-                        if let Some(clipboard) = app.data.clipboard.as_mut() {
-                            clipboard.set_text(app.labels[LABEL_TITLES[i]].clone()).unwrap();
-                            app.labels.notification = format!("{} copied!", LABEL_TITLES[i]);
-                        } else {
-                            app.labels.notification = String::from("Can't find clipboard!");
-                        }
+                        let text = app.labels[LABEL_TITLES[i]].clone();
+                        copy_to_clipboard(&mut app.data, &mut app.labels, text, LABEL_TITLES[i]);
                     }
                 }
                 Window::Hex
                 | Window::Ascii
                 | Window::Unhandled
+                | Window::BookmarkLabel
+                | Window::BookmarkList
+                | Window::Checksums
+                | Window::CloseTab
+                | Window::Entropy
+                | Window::Export
+                | Window::Fill
                 | Window::JumpToByte
+                | Window::OpenFile
+                | Window::SaveAs
+                | Window::Search
+                | Window::Strings
+                | Window::Reload
                 | Window::UnsavedChanges => {}
             }
         }
@@ -299,6 +488,51 @@ pub(crate) fn handle_mouse_input(app: &mut Application, mouse: MouseEvent) {
     }
 }
 
+/// Selects the bytes from where the drag started to the one under the mouse in the editor that
+/// was clicked last.
+fn handle_mouse_drag(app: &mut Application, mouse: MouseEvent) {
+    match app.data.last_click {
+        Window::Ascii => {
+            if let Some((cursor_pos, _)) = handle_editor_drag(Window::Ascii, app, mouse) {
+                if app.data.last_drag.is_none() {
+                    app.data.last_drag = Some(app.data.offset);
+                }
+                app.data.offset = cursor_pos;
+                app.labels.update_all(&app.data.contents[app.data.offset..], app.data.endian);
+                adjust_offset(&mut app.data, &mut app.display, &mut app.labels);
+            }
+        }
+        Window::Hex => {
+            if let Some((cursor_pos, nibble)) = handle_editor_drag(Window::Hex, app, mouse) {
+                if app.data.last_drag.is_none() {
+                    app.data.last_drag = Some(app.data.offset);
+                    app.data.drag_nibble = Some(app.data.nibble);
+                }
+                app.data.offset = cursor_pos;
+                app.data.nibble = nibble.unwrap();
+                app.labels.update_all(&app.data.contents[app.data.offset..], app.data.endian);
+                adjust_offset(&mut app.data, &mut app.display, &mut app.labels);
+            }
+        }
+        Window::Label(_)
+        | Window::Unhandled
+        | Window::BookmarkLabel
+        | Window::BookmarkList
+        | Window::Checksums
+        | Window::CloseTab
+        | Window::Entropy
+        | Window::Export
+        | Window::Fill
+        | Window::JumpToByte
+        | Window::OpenFile
+        | Window::SaveAs
+        | Window::Search
+        | Window::Strings
+        | Window::Reload
+        | Window::UnsavedChanges => {}
+    }
+}
+
 /// A wrapper around [`handle_cursor`] that does the additional things that come with a click.
 #[allow(clippy::cast_possible_truncation)]
 fn handle_editor_click(
@@ -396,7 +630,7 @@ fn handle_editor_drag(
                 mouse.column = end_of_row;
             }
         }
-        mouse.row = editor_bottom_row - if click_past_contents { 0 } else { 1 };
+        mouse.row = editor_bottom_row - u16::from(!click_past_contents);
         if let Some(mut result) = handle_editor_cursor_action(window, app, mouse) {
             if let Some(new_y) = result.0.checked_add(app.display.comp_layouts.bytes_per_line) {
                 if new_y < app.data.contents.len() {
@@ -1,9 +1,14 @@
 //! Labels in the bottom half of the terminal UI that provide information based on cursor position.
 
 #![allow(clippy::cast_possible_wrap)]
-use std::ops::Index;
+use std::{fmt::Write, mem, ops::Index};
 
-pub(crate) static LABEL_TITLES: [&str; 16] = [
+use crate::decoder::Endian;
+
+/// The number of bytes searched for the null terminator of the string at the cursor.
+const MAX_STRING_LENGTH: usize = 256;
+
+pub(crate) static LABEL_TITLES: [&str; 17] = [
     "Signed 8 bit",
     "Unsigned 8 bit",
     "Signed 16 bit",
@@ -20,8 +25,73 @@ pub(crate) static LABEL_TITLES: [&str; 16] = [
     "Float 64 bit",
     "Offset",
     "Notifications",
+    "String",
 ];
 
+/// The bytes at the cursor interpreted as different types. A value is `None` if there are not
+/// enough bytes left for it.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Inspection {
+    pub(crate) signed_eight: Option<i8>,
+    pub(crate) unsigned_eight: Option<u8>,
+    pub(crate) signed_sixteen: Option<i16>,
+    pub(crate) unsigned_sixteen: Option<u16>,
+    pub(crate) signed_thirtytwo: Option<i32>,
+    pub(crate) unsigned_thirtytwo: Option<u32>,
+    pub(crate) signed_sixtyfour: Option<i64>,
+    pub(crate) unsigned_sixtyfour: Option<u64>,
+    pub(crate) float_thirtytwo: Option<f32>,
+    pub(crate) float_sixtyfour: Option<f64>,
+
+    /// The bytes up to the next null byte, which is `None` if there is none within
+    /// [`MAX_STRING_LENGTH`] bytes.
+    pub(crate) string: Option<String>,
+}
+
+/// Reads a number of type `$type` from the start of `$bytes`, if there are enough of them.
+macro_rules! read {
+    ($type:ty, $bytes:expr, $endian:expr) => {
+        $bytes.get(..mem::size_of::<$type>()).map(|bytes| {
+            let bytes = bytes.try_into().unwrap();
+            match $endian {
+                Endian::Little => <$type>::from_le_bytes(bytes),
+                Endian::Big => <$type>::from_be_bytes(bytes),
+            }
+        })
+    };
+}
+
+/// Interprets the start of `bytes` as each of the types of the [`Inspection`].
+pub(crate) fn inspect(bytes: &[u8], endian: Endian) -> Inspection {
+    let string = bytes[..bytes.len().min(MAX_STRING_LENGTH)]
+        .iter()
+        .position(|&byte| byte == 0)
+        .map(|end| String::from_utf8_lossy(&bytes[..end]).into_owned());
+    Inspection {
+        signed_eight: read!(i8, bytes, endian),
+        unsigned_eight: read!(u8, bytes, endian),
+        signed_sixteen: read!(i16, bytes, endian),
+        unsigned_sixteen: read!(u16, bytes, endian),
+        signed_thirtytwo: read!(i32, bytes, endian),
+        unsigned_thirtytwo: read!(u32, bytes, endian),
+        signed_sixtyfour: read!(i64, bytes, endian),
+        unsigned_sixtyfour: read!(u64, bytes, endian),
+        float_thirtytwo: read!(f32, bytes, endian),
+        float_sixtyfour: read!(f64, bytes, endian),
+        string,
+    }
+}
+
+/// Formats `value` for a label, leaving it empty if there is no value.
+fn format_value<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Formats a float for a label in scientific notation, see [`format_value`].
+fn format_float<T: std::fmt::LowerExp>(value: Option<T>) -> String {
+    value.map(|value| format!("{value:e}")).unwrap_or_default()
+}
+
 #[derive(Default)]
 pub(crate) struct LabelHandler {
     signed_eight: String,
@@ -39,6 +109,7 @@ pub(crate) struct LabelHandler {
     hexadecimal: String,
     stream_length: usize,
     stream_length_string: String,
+    string: String,
     pub(crate) offset: String,
     pub(crate) notification: String,
 }
@@ -64,6 +135,7 @@ impl Index<&str> for LabelHandler {
             "Float 64 bit" => &self.float_sixtyfour,
             "Offset" => &self.offset,
             "Notifications" => &self.notification,
+            "String" => &self.string,
             _ => panic!(),
         }
     }
@@ -73,24 +145,28 @@ impl LabelHandler {
     pub(crate) fn new(bytes: &[u8]) -> Self {
         let mut labels = Self { ..Default::default() };
         labels.update_stream_length(8);
-        labels.update_all(bytes);
+        labels.update_all(bytes, Endian::default());
         labels.offset = String::from("0x0");
         labels
     }
-    pub(crate) fn update_all(&mut self, bytes: &[u8]) {
-        let filled_bytes = fill_slice(bytes, 8);
-        self.update_signed_eight(&filled_bytes[0..1]);
-        self.update_signed_sixteen(&filled_bytes[0..2]);
-        self.update_signed_thirtytwo(&filled_bytes[0..4]);
-        self.update_signed_sixtyfour(&filled_bytes[0..8]);
+    pub(crate) fn update_all(&mut self, bytes: &[u8], endian: Endian) {
+        let inspection = inspect(bytes, endian);
+        self.signed_eight = format_value(inspection.signed_eight);
+        self.signed_sixteen = format_value(inspection.signed_sixteen);
+        self.signed_thirtytwo = format_value(inspection.signed_thirtytwo);
+        self.signed_sixtyfour = format_value(inspection.signed_sixtyfour);
+
+        self.unsigned_eight = format_value(inspection.unsigned_eight);
+        self.unsigned_sixteen = format_value(inspection.unsigned_sixteen);
+        self.unsigned_thirtytwo = format_value(inspection.unsigned_thirtytwo);
+        self.unsigned_sixtyfour = format_value(inspection.unsigned_sixtyfour);
 
-        self.update_unsigned_eight(&filled_bytes[0..1]);
-        self.update_unsigned_sixteen(&filled_bytes[0..2]);
-        self.update_unsigned_thirtytwo(&filled_bytes[0..4]);
-        self.update_unsigned_sixtyfour(&filled_bytes[0..8]);
+        self.float_thirtytwo = format_float(inspection.float_thirtytwo);
+        self.float_sixtyfour = format_float(inspection.float_sixtyfour);
 
-        self.update_float_thirtytwo(&filled_bytes[0..4]);
-        self.update_float_sixtyfour(&filled_bytes[0..8]);
+        // Escaping keeps control characters from breaking up the label.
+        self.string =
+            inspection.string.map(|string| string.escape_debug().to_string()).unwrap_or_default();
 
         self.update_streams(bytes);
     }
@@ -115,44 +191,14 @@ impl LabelHandler {
     pub(crate) const fn get_stream_length(&self) -> usize {
         self.stream_length
     }
-    fn update_signed_eight(&mut self, bytes: &[u8]) {
-        self.signed_eight = (bytes[0] as i8).to_string();
-    }
-    fn update_signed_sixteen(&mut self, bytes: &[u8]) {
-        self.signed_sixteen = i16::from_le_bytes(bytes.try_into().unwrap()).to_string();
-    }
-    fn update_signed_thirtytwo(&mut self, bytes: &[u8]) {
-        self.signed_thirtytwo = i32::from_le_bytes(bytes.try_into().unwrap()).to_string();
-    }
-    fn update_signed_sixtyfour(&mut self, bytes: &[u8]) {
-        self.signed_sixtyfour = i64::from_le_bytes(bytes.try_into().unwrap()).to_string();
-    }
-    fn update_unsigned_eight(&mut self, bytes: &[u8]) {
-        self.unsigned_eight = (bytes[0] as u8).to_string();
-    }
-    fn update_unsigned_sixteen(&mut self, bytes: &[u8]) {
-        self.unsigned_sixteen = u16::from_le_bytes(bytes.try_into().unwrap()).to_string();
-    }
-    fn update_unsigned_thirtytwo(&mut self, bytes: &[u8]) {
-        self.unsigned_thirtytwo = u32::from_le_bytes(bytes.try_into().unwrap()).to_string();
-    }
-    fn update_unsigned_sixtyfour(&mut self, bytes: &[u8]) {
-        self.unsigned_sixtyfour = u64::from_le_bytes(bytes.try_into().unwrap()).to_string();
-    }
-    fn update_float_thirtytwo(&mut self, bytes: &[u8]) {
-        self.float_thirtytwo = format!("{:e}", f32::from_le_bytes(bytes.try_into().unwrap()));
-    }
-    fn update_float_sixtyfour(&mut self, bytes: &[u8]) {
-        self.float_sixtyfour = format!("{:e}", f64::from_le_bytes(bytes.try_into().unwrap()));
-    }
     fn update_binary(&mut self, bytes: &[u8]) {
-        self.binary = bytes
-            .iter()
-            .map(|byte| format!("{byte:08b}"))
-            .collect::<String>()
-            .chars()
-            .take(self.stream_length)
-            .collect();
+        let mut binary = String::new();
+        for byte in bytes.iter().take((self.stream_length + 7) / 8) {
+            // Writing to a string can't fail.
+            let _ = write!(binary, "{byte:08b}");
+        }
+        binary.truncate(self.stream_length);
+        self.binary = binary;
     }
     fn update_octal(&mut self, bytes: &[u8]) {
         self.octal =
@@ -174,3 +220,56 @@ fn fill_slice(bytes: &[u8], len: usize) -> Vec<u8> {
     }
     bytes[0..len].to_vec()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BYTES: &[u8] = b"\x00\x00\x00\x00\x00\x00\xF0\x3F\x01\x02hi\x00";
+
+    #[test]
+    fn test_inspect_little_endian() {
+        let inspection = inspect(&BYTES[4..], Endian::Little);
+        assert_eq!(inspection.signed_thirtytwo, Some(0x3FF0_0000));
+        assert_eq!(inspection.unsigned_sixteen, Some(0));
+
+        let inspection = inspect(BYTES, Endian::Little);
+        assert_eq!(inspection.float_sixtyfour, Some(1.0));
+        assert_eq!(inspection.string, Some(String::new()));
+    }
+
+    #[test]
+    fn test_inspect_big_endian() {
+        let inspection = inspect(&BYTES[4..], Endian::Big);
+        assert_eq!(inspection.signed_thirtytwo, Some(0x0000_F03F));
+        assert_eq!(inspection.signed_sixteen, Some(0));
+
+        let inspection = inspect(b"\x3F\xF0\x00\x00\x00\x00\x00\x00", Endian::Big);
+        assert_eq!(inspection.float_sixtyfour, Some(1.0));
+        assert_eq!(inspect(b"no terminator", Endian::Big).string, None);
+    }
+
+    #[test]
+    fn test_inspect_byte_swapped() {
+        let little = inspect(BYTES, Endian::Little);
+        let big = inspect(BYTES, Endian::Big);
+        assert_eq!(little.unsigned_sixteen.map(u16::swap_bytes), big.unsigned_sixteen);
+        assert_eq!(little.unsigned_thirtytwo.map(u32::swap_bytes), big.unsigned_thirtytwo);
+        assert_eq!(little.unsigned_sixtyfour.map(u64::swap_bytes), big.unsigned_sixtyfour);
+        assert_eq!(little.unsigned_eight, big.unsigned_eight);
+        assert_eq!(little.string, big.string);
+    }
+
+    #[test]
+    fn test_inspect_not_enough_bytes() {
+        let inspection = inspect(&BYTES[8..], Endian::Little);
+        assert_eq!(inspection.signed_eight, Some(1));
+        assert_eq!(inspection.unsigned_sixteen, Some(0x0201));
+        assert_eq!(inspection.signed_thirtytwo, Some(0x6968_0201));
+        assert_eq!(inspection.signed_sixtyfour, None);
+        assert_eq!(inspection.float_sixtyfour, None);
+        assert_eq!(inspection.string, Some(String::from("\x01\x02hi")));
+
+        assert_eq!(inspect(&[], Endian::Big), Inspection::default());
+    }
+}
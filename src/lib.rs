@@ -0,0 +1,75 @@
+//! The HEx Helper is a cross-platform terminal UI used for modifying file data in hex or ASCII.
+//!
+//! Besides the [`Application`](app::Application) that drives the terminal UI, the library exposes
+//! the [`decoder`] module so the byte-to-glyph logic can be reused outside of heh.
+//!
+//! The decoders only need `core` and `alloc`. Building without the default `std` feature leaves out
+//! everything else, so that they can be used with `#![no_std]`, e.g. on embedded targets or WASM.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod app;
+pub mod character;
+pub mod decoder;
+
+#[cfg(feature = "std")]
+mod bookmarks;
+#[cfg(feature = "std")]
+mod buffer;
+#[cfg(feature = "std")]
+mod clipboard;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod dirty;
+#[cfg(feature = "std")]
+mod entropy;
+#[cfg(feature = "std")]
+mod export;
+#[cfg(feature = "std")]
+mod fill;
+#[cfg(feature = "std")]
+mod hash;
+#[cfg(feature = "std")]
+mod history;
+#[cfg(feature = "std")]
+mod input;
+#[cfg(feature = "std")]
+mod label;
+#[cfg(feature = "std")]
+mod paste;
+#[cfg(feature = "std")]
+mod save;
+#[cfg(feature = "std")]
+mod screen;
+#[cfg(feature = "std")]
+mod search;
+#[cfg(feature = "std")]
+mod strings;
+#[cfg(feature = "std")]
+mod tabs;
+#[cfg(feature = "std")]
+mod theme;
+#[cfg(feature = "std")]
+mod watch;
+#[cfg(feature = "std")]
+mod windows;
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use crate::decoder::{ByteAlignedDecoder, Encoding};
+
+    /// The decoders are usable without `std`, which `cargo test --no-default-features` checks.
+    #[test]
+    fn test_decode_without_std() {
+        let decoded: String = ByteAlignedDecoder::new("a€\x00".as_bytes(), Encoding::Utf8)
+            .map(|character| character.escape())
+            .collect();
+        assert_eq!(decoded, "a€••0");
+    }
+}
@@ -5,23 +5,19 @@
 //! **heh is currently in alpha** - it's not ready to be used in any production manner. Notably, it
 //! does not store backups if killed or crashing and there is no undo option after deleting a byte.
 
-use std::{error::Error, fs::OpenOptions, io, process};
+use std::{
+    env,
+    error::Error,
+    fs::{self, OpenOptions},
+    io,
+    path::PathBuf,
+    process,
+};
 
 use clap::{arg_enum, command, value_t, Arg};
 use crossterm::tty::IsTty;
 
-use app::Application;
-
-use crate::decoder::Encoding;
-
-mod app;
-mod character;
-mod chunk;
-mod decoder;
-mod input;
-mod label;
-mod screen;
-mod windows;
+use heh::{app::Application, decoder::Encoding};
 
 const ABOUT: &str = "
 A HEx Helper to edit bytes by the nibble.
@@ -43,20 +39,72 @@ Like GHex, you cannot create files with heh, only modify them.
 Terminal UI Commands:
     ALT=                Increase the stream length by 1
     ALT-                Decrease the stream length by 1
+    ALTb                Toggle the byte order (shown as LE/BE)
+    ALTr                Cycle the bytes per row (auto, 8, 16, 24, 32)
+    ALTa                Cycle the base of the addresses (hex, decimal, octal)
+    ALTe                Switch to the previous encoding
+    CNTRLe              Switch to the next encoding
     CNTRLs              Save
+    CNTRLa              Save as another file, which is edited from then on
+    CNTRLr              Toggle read-only mode (shown as [RO]), unless
+                        opened with --read-only
     CNTRLq              Quit
-    CNTRLj              Jump to Byte
+    CNTRLo              Open another file in a new tab
+    CNTRLw              Close the file in the active tab
+    CNTRLTAB            Switch to the next tab (also CNTRLPAGEDOWN)
+    CNTRL+SHIFT+TAB     Switch to the previous tab (also CNTRLPAGEUP)
+    CNTRLz              Undo
+    INSERT              Toggle between inserting and overwriting bytes
+    DELETE              Delete the byte under the cursor
+    CNTRLy              Redo
+    CNTRLj              Jump to Byte (also g or : in the hex editor),
+                        relative to the cursor if preceded with + or -
+    CNTRLf              Search for hex bytes (0x...) or text
+    CNTRLn              Jump to the next match
+    CNTRLp              Jump to the previous match
+    CNTRLb              Bookmark the cursor with an optional label,
+                        or remove its bookmark
+    CNTRLl              List the bookmarks to jump to one
+    ALTn                Jump to the next bookmark
+    ALTp                Jump to the previous bookmark
+    ALTj                Jump to the next control or undecodable character
+    ALTk                Jump to the previous control or undecodable character
+    SHIFT+ARROWS        Select bytes from the cursor
+    CNTRLc              Copy the selection as hex (DE AD BE EF)
+    ALTc                Copy the selection as raw bytes, i.e. as text in
+                        the encoding if all of them can be decoded
+    CNTRLx              Copy the selection as a C, Rust, or Python literal
+    ALTh                Show and copy checksums of the selection or file
+    ALTt                Show the entropy and byte frequencies
+    ALTs                List the strings of printable text to jump to one
+    CNTRLv              Paste hex (de ad, 0xDE, ...) or text at the cursor
+    ALTv                Paste text at the cursor, even if it looks like hex
+    ALTf                Fill the selection with a repeating byte pattern
 
 Left-clicking on a label will copy the contents to the clipboard.
 Left-clicking on the ASCII or hex table will focus it.
 
-Zooming in and out will change the size of the components.";
+Zooming in and out will change the size of the components.
+
+If the file is changed by another program while it is open, heh
+offers to reload it: press R to reload, or Esc to keep editing.
+
+The colors can be changed with key = \"color\" pairs in
+~/.config/heh/theme.toml. The keys are offset, hex_normal,
+hex_modified, ascii_printable, ascii_control, ascii_unknown,
+selection, cursor, and bookmark; colors are named (e.g. light_red),
+written as #rrggbb, or a number from 0 to 255.";
 
 arg_enum! {
     #[derive(Copy, Clone, Debug)]
     pub enum EncodingOption {
         Ascii,
         Utf8,
+        Utf16Le,
+        Utf16Be,
+        Latin1,
+        Cp437,
+        Ebcdic,
     }
 }
 
@@ -65,10 +113,31 @@ impl From<EncodingOption> for Encoding {
         match encoding {
             EncodingOption::Ascii => Encoding::Ascii,
             EncodingOption::Utf8 => Encoding::Utf8,
+            EncodingOption::Utf16Le => Encoding::Utf16Le,
+            EncodingOption::Utf16Be => Encoding::Utf16Be,
+            EncodingOption::Latin1 => Encoding::Latin1,
+            EncodingOption::Cp437 => Encoding::Cp437,
+            EncodingOption::Ebcdic => Encoding::Ebcdic,
         }
     }
 }
 
+/// Parses the number of bytes per row, where "auto" fits as many as possible.
+fn parse_bytes_per_row(value: &str) -> Result<Option<usize>, String> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(None);
+    }
+    match value.parse() {
+        Ok(0) | Err(_) => Err(String::from("expected a positive number or auto")),
+        Ok(bytes_per_row) => Ok(Some(bytes_per_row)),
+    }
+}
+
+/// The theme file that the colors are loaded from, `~/.config/heh/theme.toml`.
+fn theme_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".config/heh/theme.toml"))
+}
+
 /// Opens the specified file, creates a new application and runs it!
 fn main() -> Result<(), Box<dyn Error>> {
     let matches = command!()
@@ -76,13 +145,37 @@ fn main() -> Result<(), Box<dyn Error>> {
         .long_about(LONG_ABOUT)
         .arg(
             Arg::new("Encoding")
-                .help("Encoding used for text editor")
+                .help("Encoding used for text editor, detected from the byte order mark by default")
                 .short('e')
                 .long("encoding")
                 .required(false)
                 .case_insensitive(true)
-                .possible_values(EncodingOption::variants())
-                .default_value("Ascii"),
+                .possible_values(EncodingOption::variants()),
+        )
+        .arg(
+            Arg::new("Diff")
+                .help("Another file to highlight the bytes that differ from at the same offsets")
+                .short('d')
+                .long("diff")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::new("BytesPerRow")
+                .help("Number of bytes in each row, or auto to fit as many as possible")
+                .short('r')
+                .long("bytes-per-row")
+                .value_name("N")
+                .takes_value(true)
+                .required(false)
+                .validator(parse_bytes_per_row),
+        )
+        .arg(
+            Arg::new("ReadOnly")
+                .help("Open the file without allowing any edits or saving")
+                .long("read-only")
+                .required(false),
         )
         .arg(Arg::new("FILE").required(true))
         .get_matches();
@@ -92,13 +185,34 @@ fn main() -> Result<(), Box<dyn Error>> {
         process::exit(1);
     }
 
-    let file = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(matches.get_one::<String>("FILE").unwrap())?;
-    let encoding = value_t!(matches, "Encoding", EncodingOption)?;
-
-    let mut app = Application::new(file, encoding.into())?;
+    let read_only = matches.is_present("ReadOnly");
+    let path = matches.get_one::<String>("FILE").unwrap();
+    let file = OpenOptions::new().read(true).write(!read_only).open(path)?;
+    let encoding = if matches.is_present("Encoding") {
+        Some(value_t!(matches, "Encoding", EncodingOption)?.into())
+    } else {
+        None
+    };
+
+    let mut app = if read_only {
+        Application::new_read_only(file, encoding)?
+    } else {
+        Application::new(file, encoding)?
+    };
+    app.set_path(path);
+    if let Some(bytes_per_row) = matches.get_one::<String>("BytesPerRow") {
+        app.set_bytes_per_row(parse_bytes_per_row(bytes_per_row)?);
+    }
+    if let Some(other) = matches.get_one::<String>("Diff") {
+        app.compare_with(fs::read(other)?);
+    }
+    if let Some(path) = theme_path() {
+        match fs::read_to_string(&path) {
+            Ok(theme) => app.set_theme(&theme).map_err(|e| format!("{}: {e}", path.display()))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
     app.run()?;
 
     Ok(())
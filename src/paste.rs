@@ -0,0 +1,123 @@
+//! Turning the text on the clipboard into the bytes to paste.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::decoder::Encoding;
+
+/// How the text on the clipboard is turned into bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PasteMode {
+    /// As hex bytes if the text only consists of them, or as text in the encoding otherwise. The
+    /// bytes may be separated by whitespace and commas and prefixed with "0x".
+    Detect(Encoding),
+    /// As text in the encoding.
+    Text(Encoding),
+}
+
+/// The reasons the text on the clipboard can't be pasted.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ParseError {
+    /// There is nothing to paste.
+    Empty,
+    /// A hex byte is missing its second digit.
+    IncompleteByte,
+    /// A character is neither a hex digit nor a separator.
+    InvalidHex(char),
+    /// A character cannot be represented in the encoding.
+    Unencodable(char, Encoding),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "Nothing to paste!"),
+            ParseError::IncompleteByte => write!(f, "Hex needs full bytes!"),
+            ParseError::InvalidHex(c) => write!(f, "Invalid Hex: {c}"),
+            ParseError::Unencodable(c, encoding) => write!(f, "Can't encode {c} in {encoding}!"),
+        }
+    }
+}
+
+/// Turns `s` into the bytes to paste.
+///
+/// Hex bytes are written in pairs of digits, but don't need to be separated (i.e. "deadbeef",
+/// "de ad be ef", and "0xDE, 0xAD, 0xBE, 0xEF" are all the same). As hex is detected by its digits
+/// only, text like "cafe" is pasted as hex unless [`PasteMode::Text`] is used.
+pub(crate) fn parse_paste(s: &str, mode: PasteMode) -> Result<Vec<u8>, ParseError> {
+    if s.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    match mode {
+        PasteMode::Detect(encoding) => parse_hex(s).or_else(|_| encode(s, encoding)),
+        PasteMode::Text(encoding) => encode(s, encoding),
+    }
+}
+
+fn parse_hex(s: &str) -> Result<Vec<u8>, ParseError> {
+    let mut digits = vec![];
+    for token in s.split(|c: char| c.is_whitespace() || c == ',') {
+        let token = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token);
+        for c in token.chars() {
+            let digit = c.to_digit(16).and_then(|digit| u8::try_from(digit).ok());
+            digits.push(digit.ok_or(ParseError::InvalidHex(c))?);
+        }
+    }
+    if digits.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    if digits.len() % 2 != 0 {
+        return Err(ParseError::IncompleteByte);
+    }
+    Ok(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+fn encode(s: &str, encoding: Encoding) -> Result<Vec<u8>, ParseError> {
+    let mut bytes = vec![];
+    for c in s.chars() {
+        bytes.extend(encoding.encode_char(c).ok_or(ParseError::Unencodable(c, encoding))?);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DETECT: PasteMode = PasteMode::Detect(Encoding::Ascii);
+
+    #[test]
+    fn test_hex() {
+        let dead_beef = Ok(vec![0xDE, 0xAD, 0xBE, 0xEF]);
+        assert_eq!(parse_paste("deadbeef", DETECT), dead_beef);
+        assert_eq!(parse_paste("de ad be ef", DETECT), dead_beef);
+        assert_eq!(parse_paste("DE AD\n BE\tEF\n", DETECT), dead_beef);
+        assert_eq!(parse_paste("dead beef", DETECT), dead_beef);
+
+        assert_eq!(parse_hex("dea"), Err(ParseError::IncompleteByte));
+        assert_eq!(parse_hex("de ag"), Err(ParseError::InvalidHex('g')));
+        assert_eq!(parse_hex(" , "), Err(ParseError::Empty));
+        assert_eq!(parse_paste("", DETECT), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn test_prefixed_hex() {
+        let dead_beef = Ok(vec![0xDE, 0xAD, 0xBE, 0xEF]);
+        assert_eq!(parse_paste("0xDE, 0xAD, 0xBE, 0xEF", DETECT), dead_beef);
+        assert_eq!(parse_paste("0xde,0xad,0xbe,0xef", DETECT), dead_beef);
+        assert_eq!(parse_paste("0XDEAD 0xBEEF", DETECT), dead_beef);
+        assert_eq!(parse_hex("{ 0xDE, 0xAD }"), Err(ParseError::InvalidHex('{')));
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(parse_paste("hello", DETECT), Ok(b"hello".to_vec()));
+        assert_eq!(parse_paste("0xDE, 0xA", DETECT), Ok(b"0xDE, 0xA".to_vec()));
+        assert_eq!(parse_paste("cafe", DETECT), Ok(vec![0xCA, 0xFE]));
+        assert_eq!(parse_paste("cafe", PasteMode::Text(Encoding::Ascii)), Ok(b"cafe".to_vec()));
+        assert_eq!(
+            parse_paste("hé", PasteMode::Text(Encoding::Utf16Le)),
+            Ok(vec![b'h', 0, 0xE9, 0])
+        );
+        assert_eq!(parse_paste("hé", DETECT), Err(ParseError::Unencodable('é', Encoding::Ascii)));
+    }
+}
@@ -0,0 +1,127 @@
+//! Writing the contents back to disk without the risk of leaving a half-written file behind.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// Replaces the file at `path` with `bytes`, or creates it if it doesn't exist.
+///
+/// The bytes are written to a temporary file in the same directory first, which is then renamed
+/// over the original one. Renaming is atomic, so the file is either replaced as a whole or not at
+/// all, even if heh crashes midway. The new file gets the permissions of the one it replaces, and
+/// a symbolic link is replaced at its target rather than turned into a file.
+pub(crate) fn save_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    replace_with(path, |file| file.write_all(bytes))
+}
+
+/// Like [`save_atomic`], but writes the temporary file with `write`. The temporary file is removed
+/// again if anything fails.
+fn replace_with(path: &Path, write: impl FnOnce(&mut File) -> io::Result<()>) -> io::Result<()> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+    let temp = temp_path(&path)?;
+    let result = write_temp(&path, &temp, write).and_then(|()| fs::rename(&temp, &path));
+    if result.is_err() {
+        // The error that stopped the save is more helpful than one from cleaning up after it.
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// The path of the temporary file that replaces the one at `path`, which is hidden next to it.
+fn temp_path(path: &Path) -> io::Result<PathBuf> {
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("{} isn't a file", path.display()))
+    })?;
+    let temp = format!(".{}.heh-{}", name.to_string_lossy(), process::id());
+    Ok(path.with_file_name(temp))
+}
+
+fn write_temp(
+    path: &Path,
+    temp: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(temp)?;
+    write(&mut file)?;
+    file.sync_all()?;
+    match fs::metadata(path) {
+        Ok(metadata) => fs::set_permissions(temp, metadata.permissions()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    /// A directory of its own for each test, so that leftover files can be spotted.
+    fn test_dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("heh-test-save-{test}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        dir
+    }
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut entries = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn test_save() {
+        let dir = test_dir("new");
+        let path = dir.join("data.bin");
+        save_atomic(&path, b"first").unwrap();
+        save_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert_eq!(entries(&dir), ["data.bin"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cleanup_on_error() {
+        let dir = test_dir("error");
+        let path = dir.join("data.bin");
+        fs::write(&path, b"original").unwrap();
+
+        let error = replace_with(&path, |file| {
+            file.write_all(b"half")?;
+            Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+        });
+        assert_eq!(error.unwrap_err().to_string(), "disk full");
+        assert_eq!(fs::read(&path).unwrap(), b"original");
+        assert_eq!(entries(&dir), ["data.bin"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = test_dir("permissions");
+        let path = dir.join("script.sh");
+        fs::write(&path, b"#!/bin/sh").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o751)).unwrap();
+        save_atomic(&path, b"#!/bin/sh\necho hi").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o751);
+
+        // A link keeps pointing at the saved file.
+        let link = dir.join("link.sh");
+        symlink(&path, &link).unwrap();
+        save_atomic(&link, b"#!/bin/sh").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read(&path).unwrap(), b"#!/bin/sh");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
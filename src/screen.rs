@@ -3,7 +3,9 @@
 use std::{
     cmp,
     error::Error,
+    fmt::{self, Display, Formatter},
     io::{self, Stdout},
+    ops::Range,
 };
 
 use crossterm::{
@@ -14,33 +16,99 @@ use crossterm::{
 use tui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Span, Spans, Text},
     widgets::{Block, Borders, Clear, Paragraph},
     Terminal,
 };
 
-use crate::chunk::OverlappingChunks;
 use crate::{
     app::{AppData, Nibble},
-    decoder::ByteAlignedDecoder,
+    buffer::Buffer,
+    character::{Category, RichChar, Type},
+    decoder::{char_span, detect_bom, Encoding, Endian, RowDecoder, RowItem},
+    diff::differs_at,
     label::{LabelHandler, LABEL_TITLES},
+    tabs::Tabs,
+    theme::Theme,
     windows::{editor::Editor, KeyHandler, Window},
 };
 
-const COLOR_NULL: Color = Color::DarkGray;
+const COLOR_MATCH: Color = Color::Blue;
+const COLOR_DIFF: Color = Color::Red;
+
+/// The fewest digits that addresses are shown with, which is enough for files up to 4 GiB in hex.
+const MIN_ADDRESS_DIGITS: usize = 8;
+
+/// The bases that the addresses in the address table can be shown in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AddressBase {
+    Hex,
+    Decimal,
+    Octal,
+}
+
+impl AddressBase {
+    fn radix(self) -> usize {
+        match self {
+            AddressBase::Hex => 16,
+            AddressBase::Decimal => 10,
+            AddressBase::Octal => 8,
+        }
+    }
+
+    /// The base after this one, wrapping around after octal.
+    pub(crate) fn next(self) -> Self {
+        match self {
+            AddressBase::Hex => AddressBase::Decimal,
+            AddressBase::Decimal => AddressBase::Octal,
+            AddressBase::Octal => AddressBase::Hex,
+        }
+    }
+}
+
+impl Display for AddressBase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressBase::Hex => write!(f, "Hex"),
+            AddressBase::Decimal => write!(f, "Decimal"),
+            AddressBase::Octal => write!(f, "Octal"),
+        }
+    }
+}
+
+/// The numbers of bytes per row that can be cycled through, besides fitting as many as possible.
+pub(crate) const BYTES_PER_ROW_PRESETS: [usize; 4] = [8, 16, 24, 32];
 
 pub(crate) struct ScreenHandler {
     terminal: Terminal<CrosstermBackend<Stdout>>,
     pub(crate) terminal_size: Rect,
     pub(crate) comp_layouts: ComponentLayouts,
+
+    /// The number of bytes shown in each row, or `None` to fit as many as the terminal is wide
+    /// enough for.
+    pub(crate) bytes_per_row: Option<usize>,
+
+    /// Whether the tab bar is shown above the editors, which it is while several files are open.
+    pub(crate) tab_bar: bool,
+
+    /// The colors of the address table and the editors.
+    pub(crate) theme: Theme,
+
+    /// The base the addresses are shown in.
+    pub(crate) address_base: AddressBase,
+
+    /// The number of digits the addresses are shown with, which grows with the file.
+    address_digits: usize,
 }
 
 pub(crate) struct ComponentLayouts {
+    tab_bar: Rect,
     line_numbers: Rect,
     pub(crate) hex: Rect,
     pub(crate) ascii: Rect,
     labels: Vec<Rect>,
+    status_bar: Rect,
     pub(crate) popup: Rect,
     pub(crate) bytes_per_line: usize,
     pub(crate) lines_per_screen: usize,
@@ -53,7 +121,18 @@ impl ScreenHandler {
         Ok(Self {
             terminal,
             terminal_size,
-            comp_layouts: Self::calculate_dimensions(terminal_size, &Editor::Hex),
+            comp_layouts: Self::calculate_dimensions(
+                terminal_size,
+                &Editor::Hex,
+                None,
+                false,
+                MIN_ADDRESS_DIGITS,
+            ),
+            bytes_per_row: None,
+            tab_bar: false,
+            theme: Theme::default(),
+            address_base: AddressBase::Hex,
+            address_digits: MIN_ADDRESS_DIGITS,
         })
     }
     pub(crate) fn setup() -> Result<(), Box<dyn Error>> {
@@ -92,25 +171,43 @@ impl ScreenHandler {
 
     /// Calculates the dimensions of the components that will be continually displayed.
     ///
-    /// This includes the editors, labels, and address table.
-    fn calculate_dimensions(frame: Rect, window: &dyn KeyHandler) -> ComponentLayouts {
+    /// This includes the editors, labels, and address table. The editors are as wide as needed to
+    /// show `bytes_per_row` bytes in each row, which is limited to what fits in the frame. If it is
+    /// `None`, as many as fit are shown. The tab bar takes up the first row if `tab_bar` is set, and
+    /// the address table is wide enough for addresses of `address_digits` digits.
+    fn calculate_dimensions(
+        frame: Rect,
+        window: &dyn KeyHandler,
+        bytes_per_row: Option<usize>,
+        tab_bar: bool,
+        address_digits: usize,
+    ) -> ComponentLayouts {
         // Establish Constraints
         let sections = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(3), Constraint::Length(12)])
+            .constraints([
+                Constraint::Length(u16::from(tab_bar)),
+                Constraint::Min(3),
+                Constraint::Length(15),
+                Constraint::Length(1),
+            ])
             .split(frame);
+        let bytes_per_line = fit_bytes_per_row(bytes_per_row, frame.width, address_digits);
         let editors = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
-                Constraint::Length(10),
-                // The address table is Length(10) as specified above. Because the hex editor takes
-                // 3 graphemes for every 1 that ASCII takes (each nibble plus a space), we multiply
-                // the editors by those ratios.
-                Constraint::Length((frame.width - 10) * 3 / 4 - 1),
-                Constraint::Length((frame.width - 10) / 4),
+                Constraint::Length(address_width(address_digits)),
+                Constraint::Length(hex_width(bytes_per_line)),
+                Constraint::Length(text_width(bytes_per_line)),
+                // Whatever width is left over stays empty.
+                Constraint::Min(0),
             ])
-            .split(sections[0]);
-        let mut labels = Vec::with_capacity(12);
+            .split(sections[1]);
+        let mut labels = Vec::with_capacity(LABEL_TITLES.len());
+        let label_rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(12), Constraint::Length(3)])
+            .split(sections[2]);
         let label_cols = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
@@ -119,7 +216,7 @@ impl ScreenHandler {
                 Constraint::Ratio(1, 4),
                 Constraint::Ratio(1, 4),
             ])
-            .split(sections[1]);
+            .split(label_rows[0]);
         for label in label_cols {
             labels.append(
                 &mut Layout::default()
@@ -133,15 +230,16 @@ impl ScreenHandler {
                     .split(label),
             );
         }
+        // The string at the cursor is below the grid, as strings can be long.
+        labels.push(label_rows[1]);
 
         // Calculate popup dimensions
         let popup = Self::calculate_popup_dimensions(frame, window);
 
-        // Calculate bytes per line
-        let bytes_per_line = ((editors[1].width - 2) / 3) as usize;
         let lines_per_screen = (editors[1].height - 2) as usize;
 
         ComponentLayouts {
+            tab_bar: sections[0],
             line_numbers: editors[0],
             hex: editors[1],
             ascii: editors[2],
@@ -149,9 +247,65 @@ impl ScreenHandler {
             bytes_per_line,
             lines_per_screen,
             labels,
+            status_bar: sections[3],
         }
     }
 
+    /// Changes the number of bytes shown in each row, where `None` fits as many as possible.
+    pub(crate) fn set_bytes_per_row(
+        &mut self,
+        app_info: &mut AppData,
+        window: &dyn KeyHandler,
+        bytes_per_row: Option<usize>,
+    ) {
+        self.bytes_per_row = bytes_per_row;
+        self.comp_layouts = Self::calculate_dimensions(
+            self.terminal_size,
+            window,
+            bytes_per_row,
+            self.tab_bar,
+            self.address_digits,
+        );
+        align_start_address(app_info, self.comp_layouts.bytes_per_line);
+    }
+
+    /// Shows or hides the tab bar above the editors.
+    pub(crate) fn set_tab_bar(
+        &mut self,
+        app_info: &mut AppData,
+        window: &dyn KeyHandler,
+        shown: bool,
+    ) {
+        self.tab_bar = shown;
+        self.comp_layouts = Self::calculate_dimensions(
+            self.terminal_size,
+            window,
+            self.bytes_per_row,
+            shown,
+            self.address_digits,
+        );
+        align_start_address(app_info, self.comp_layouts.bytes_per_line);
+    }
+
+    /// Shows the addresses in `base`, widening or narrowing the address table to fit them.
+    pub(crate) fn set_address_base(
+        &mut self,
+        app_info: &mut AppData,
+        window: &dyn KeyHandler,
+        base: AddressBase,
+    ) {
+        self.address_base = base;
+        self.address_digits = address_digits(app_info.contents.len(), base);
+        self.comp_layouts = Self::calculate_dimensions(
+            self.terminal_size,
+            window,
+            self.bytes_per_row,
+            self.tab_bar,
+            self.address_digits,
+        );
+        align_start_address(app_info, self.comp_layouts.bytes_per_line);
+    }
+
     /// Calculates the dimensions of the popup that is being focused. Currently used in
     /// [`calculate_dimensions`](Self::calculate_dimensions) and
     /// [`set_focused_window`](crate::app::Application::set_focused_window)
@@ -161,12 +315,15 @@ impl ScreenHandler {
     }
 
     /// Generates all the visuals of the file contents to be displayed to user by calling
-    /// [`generate_hex`](generate_hex) and [`generate_ascii`](generate_ascii).
-    fn generate_text(
-        app_info: &mut AppData,
+    /// [`generate_hex`](generate_hex) and [`generate_decoded`](generate_decoded).
+    fn generate_text<'a>(
+        app_info: &'a mut AppData,
+        theme: &Theme,
+        address_base: AddressBase,
+        address_digits: usize,
         bytes_per_line: usize,
         lines_per_screen: usize,
-    ) -> (Text, Text, Text) {
+    ) -> (Text<'a>, Text<'a>, Text<'a>) {
         let content_lines = app_info.contents.len() / bytes_per_line + 1;
         let start_row = app_info.start_address / bytes_per_line;
 
@@ -174,17 +331,27 @@ impl ScreenHandler {
         let address_text = (0..cmp::min(lines_per_screen, content_lines - start_row))
             .map(|i| {
                 let row_address = app_info.start_address + i * bytes_per_line;
-                let mut span = Span::from(format!("{:08X?}\n", row_address));
+                let row_range = row_address..row_address + bytes_per_line;
+                let mut span = Span::styled(
+                    format_address(row_address, address_base, address_digits) + "\n",
+                    Style::default().fg(theme.offset),
+                );
                 // Highlight the address row that the cursor is in for visibility
-                if (row_address..row_address + bytes_per_line).contains(&app_info.offset) {
+                if row_range.contains(&app_info.offset) {
                     span.style = span.style.fg(Color::Black).bg(Color::White);
                 }
+                // Mark the rows that contain a bookmark
+                if app_info.bookmarks.any_in(row_range) {
+                    span.style = span.style.fg(theme.bookmark);
+                }
                 Spans::from(span)
             })
             .collect::<Vec<Spans>>();
 
-        let hex_text = generate_hex(app_info, bytes_per_line, lines_per_screen);
-        let decoded_text = generate_decoded(app_info, bytes_per_line, lines_per_screen);
+        let rows = decode_visible(app_info, bytes_per_line, lines_per_screen);
+        let cursor_char = cursor_char(app_info);
+        let hex_text = generate_hex(app_info, theme, &rows, &cursor_char, bytes_per_line);
+        let decoded_text = generate_decoded(app_info, theme, &rows, &cursor_char, bytes_per_line);
 
         (address_text.into(), hex_text.into(), decoded_text.into())
     }
@@ -197,26 +364,31 @@ impl ScreenHandler {
         app_info: &mut AppData,
         labels: &LabelHandler,
         window: &dyn KeyHandler,
+        tabs: &Tabs<AppData>,
     ) -> Result<(), Box<dyn Error>> {
+        let tab_line = tab_line(tabs, app_info);
         self.terminal.draw(|f| {
             // We check if we need to recompute the terminal size in the case that the saved off
             // variable differs from the current frame, which can occur when a terminal is resized
-            // between an event handling and a rendering.
+            // between an event handling and a rendering. Likewise, the addresses may need another
+            // digit after inserting bytes or switching to a larger file.
             let size = f.size();
-            if size != self.terminal_size {
+            let address_digits = address_digits(app_info.contents.len(), self.address_base);
+            if size != self.terminal_size || address_digits != self.address_digits {
                 self.terminal_size = size;
-                self.comp_layouts = Self::calculate_dimensions(self.terminal_size, window);
-
-                // We change the start_address here to ensure that 0 is ALWAYS the first start
-                // address. We round to preventing constant resizing always moving to 0.
-                app_info.start_address = (app_info.start_address
-                    + (self.comp_layouts.bytes_per_line / 2))
-                    / self.comp_layouts.bytes_per_line
-                    * self.comp_layouts.bytes_per_line;
+                self.address_digits = address_digits;
+                self.comp_layouts = Self::calculate_dimensions(
+                    self.terminal_size,
+                    window,
+                    self.bytes_per_row,
+                    self.tab_bar,
+                    self.address_digits,
+                );
+                align_start_address(app_info, self.comp_layouts.bytes_per_line);
             }
 
             // Check if terminal is large enough
-            if self.terminal_size.width < 50 || self.terminal_size.height < 15 {
+            if self.terminal_size.width < 50 || self.terminal_size.height < 19 {
                 let dimension_notification = Paragraph::new("Terminal dimensions must be larger!")
                     .block(Block::default())
                     .alignment(Alignment::Center);
@@ -232,12 +404,22 @@ impl ScreenHandler {
                 return;
             }
 
+            // The text pane is titled after the encoding it is decoded with.
+            let encoding_title = app_info.encoding.to_string();
             let (address_text, hex_text, ascii_text) = Self::generate_text(
                 app_info,
+                &self.theme,
+                self.address_base,
+                self.address_digits,
                 self.comp_layouts.bytes_per_line,
                 self.comp_layouts.lines_per_screen,
             );
 
+            // Render Tab Bar
+            if self.tab_bar {
+                f.render_widget(Paragraph::new(tab_line), self.comp_layouts.tab_bar);
+            }
+
             // Render Line Numbers
             f.render_widget(
                 Paragraph::new(address_text)
@@ -262,7 +444,7 @@ impl ScreenHandler {
             // Render ASCII
             f.render_widget(
                 Paragraph::new(ascii_text).block(
-                    Block::default().borders(Borders::ALL).title("ASCII").style(
+                    Block::default().borders(Borders::ALL).title(encoding_title).style(
                         if window.is_focusing(Window::Ascii) {
                             Style::default().fg(Color::Yellow)
                         } else {
@@ -282,6 +464,13 @@ impl ScreenHandler {
                 );
             }
 
+            // Render Status Bar
+            f.render_widget(
+                Paragraph::new(status_line(app_info))
+                    .style(Style::default().fg(Color::Black).bg(Color::White)),
+                self.comp_layouts.status_bar,
+            );
+
             // Render Popup
             if !window.is_focusing(Window::Hex) && !window.is_focusing(Window::Ascii) {
                 f.render_widget(Clear, self.comp_layouts.popup);
@@ -292,140 +481,366 @@ impl ScreenHandler {
     }
 }
 
-/// Display hex bytes with correct highlighting and colors by chunking the bytes into rows and
-/// formatting them into hex.
+/// The width of the hex editor showing `bytes_per_row` bytes in each row, including its borders.
+/// Each byte takes two digits and a space, except for the last one.
+#[allow(clippy::cast_possible_truncation)]
+fn hex_width(bytes_per_row: usize) -> u16 {
+    bytes_per_row.saturating_mul(3).saturating_add(1).min(usize::from(u16::MAX)) as u16
+}
+
+/// The width of the text editor showing `bytes_per_row` bytes in each row, including its borders.
+#[allow(clippy::cast_possible_truncation)]
+fn text_width(bytes_per_row: usize) -> u16 {
+    bytes_per_row.saturating_add(2).min(usize::from(u16::MAX)) as u16
+}
+
+/// The width of the address table showing addresses of `digits` digits, including its borders.
+#[allow(clippy::cast_possible_truncation)]
+fn address_width(digits: usize) -> u16 {
+    digits.saturating_add(2).min(usize::from(u16::MAX)) as u16
+}
+
+/// The number of digits needed to show every address of a file that is `len` bytes long in `base`.
 ///
-/// NOTE: In UTF-8, a character takes up to 4 bytes and thus the encoding can break at the ends of a
-/// chunk. Increasing the chunk size by 3 bytes at both ends before decoding and cropping them of
-/// afterwards solves the issue for the visible parts.
-fn generate_hex(app_info: &AppData, bytes_per_line: usize, lines_per_screen: usize) -> Vec<Spans> {
-    let initial_offset = app_info.start_address.min(3);
-    OverlappingChunks::new(
-        &app_info.contents[(app_info.start_address - initial_offset)..],
-        bytes_per_line,
-        6,
+/// The address right after the last byte is shown too, as bytes can be appended there. Addresses
+/// have at least [`MIN_ADDRESS_DIGITS`] digits, so most files keep the same layout in every base.
+fn address_digits(len: usize, base: AddressBase) -> usize {
+    let mut digits = 1;
+    let mut rest = len / base.radix();
+    while rest > 0 {
+        digits += 1;
+        rest /= base.radix();
+    }
+    digits.max(MIN_ADDRESS_DIGITS)
+}
+
+/// Formats `offset` in `base` for the address table, padded with zeros to `width` digits.
+fn format_address(offset: usize, base: AddressBase, width: usize) -> String {
+    match base {
+        AddressBase::Hex => format!("{offset:0width$X}"),
+        AddressBase::Decimal => format!("{offset:0width$}"),
+        AddressBase::Octal => format!("{offset:0width$o}"),
+    }
+}
+
+/// The width of the address table with addresses of `address_digits` digits and both editors
+/// together.
+fn required_width(bytes_per_row: usize, address_digits: usize) -> u16 {
+    address_width(address_digits)
+        .saturating_add(hex_width(bytes_per_row))
+        .saturating_add(text_width(bytes_per_row))
+}
+
+/// The number of bytes to show in each row of a frame that is `width` wide. This is
+/// `bytes_per_row` if it fits, or the most that do otherwise. At least one byte is always shown.
+fn fit_bytes_per_row(bytes_per_row: Option<usize>, width: u16, address_digits: usize) -> usize {
+    let widest = (usize::from(width.saturating_sub(required_width(0, address_digits))) / 4).max(1);
+    bytes_per_row.map_or(widest, |bytes_per_row| bytes_per_row.clamp(1, widest))
+}
+
+/// Moves the start address to the beginning of a row after the number of bytes per row changed.
+///
+/// This ensures that 0 is ALWAYS the first start address. We round to prevent constant resizing
+/// from always moving to 0.
+fn align_start_address(app_info: &mut AppData, bytes_per_line: usize) {
+    app_info.start_address =
+        (app_info.start_address + (bytes_per_line / 2)) / bytes_per_line * bytes_per_line;
+}
+
+/// The names of the open files for the tab bar, where the active one is highlighted and put in
+/// brackets.
+fn tab_line(tabs: &Tabs<AppData>, active: &AppData) -> Spans<'static> {
+    let spans = tabs
+        .iter(active)
+        .enumerate()
+        .map(|(i, tab)| {
+            if i == tabs.active() {
+                Span::styled(
+                    format!("[{}: {}]", i + 1, tab.name),
+                    Style::default().fg(Color::Black).bg(Color::White),
+                )
+            } else {
+                Span::from(format!(" {}: {} ", i + 1, tab.name))
+            }
+        })
+        .collect::<Vec<_>>();
+    Spans::from(spans)
+}
+
+/// The contents of the status bar at the bottom of the screen, which shows the settings that affect
+/// how the file is displayed and the byte under the cursor.
+fn status_line(app_info: &AppData) -> String {
+    let endian = match app_info.endian {
+        Endian::Little => "LE",
+        Endian::Big => "BE",
+    };
+    let mode = if app_info.insert_mode { "INS" } else { "OVR" };
+    // Deleting every byte leaves nothing under the cursor.
+    let cursor = describe_byte(&app_info.contents, app_info.offset, app_info.encoding)
+        .unwrap_or_else(|| format!("{:#X}", app_info.offset));
+    let differing = app_info
+        .diff
+        .as_ref()
+        .map_or_else(String::new, |_| format!(" | {} differing bytes", app_info.differing));
+    format!(
+        " {}{} | {} | {} | {} | {:#X} bytes{}",
+        if app_info.read_only { "[RO] " } else { "" },
+        mode,
+        app_info.encoding,
+        endian,
+        cursor,
+        app_info.contents.len(),
+        differing
     )
-    .take(lines_per_screen)
-    .enumerate()
-    .map(|(row, chunk)| {
-        let spans = chunk
-            .iter()
-            .zip(ByteAlignedDecoder::new(chunk, app_info.encoding))
-            .skip(initial_offset)
-            .take(bytes_per_line)
-            .enumerate()
-            .flat_map(|(col, (&byte, character))| {
-                // We don't want an extra space at the end of each row.
-                if col < bytes_per_line - 1 {
-                    format!("{byte:02X?} ")
+}
+
+/// Describes the byte at `offset` for the status bar: its offset, its value in hex, decimal,
+/// binary, and octal, and the character it is part of in `encoding` along with its kind.
+///
+/// Returns `None` if `offset` is past the end of `bytes`.
+fn describe_byte(bytes: &[u8], offset: usize, encoding: Encoding) -> Option<String> {
+    let byte = bytes.get(offset).copied()?;
+    let (character, typ) = bytes
+        .get(char_span(bytes, encoding, offset))
+        .and_then(|span| encoding.decoder(span).next())
+        .unwrap_or((char::REPLACEMENT_CHARACTER, Type::Unknown));
+    let kind = match typ {
+        Type::Ascii => "Ascii",
+        Type::Control => "Control",
+        Type::Unicode(_) => "Unicode",
+        Type::Unknown => "Unknown",
+    };
+    Some(format!(
+        "{offset:#X} ({offset}): {byte:#04X} {byte} {byte:#010b} {byte:#o} {character:?} {kind}"
+    ))
+}
+
+/// Whether the byte at `offset` differs from the file that is compared against, if any.
+fn differs(app_info: &AppData, offset: usize) -> bool {
+    app_info.diff.as_ref().map_or(false, |other| differs_at(&app_info.contents, other, offset))
+}
+
+/// The bytes of the character at the cursor if it spans several, which are underlined in both
+/// editors. This shows which cells of the text editor the byte at the cursor is part of, and which
+/// bytes the character at the cursor is decoded from.
+fn cursor_char(app_info: &AppData) -> Range<usize> {
+    // The byte order mark is shown as control characters, one for each byte.
+    let bom_len = detect_bom(&app_info.contents).map_or(0, |(_, len)| len);
+    if app_info.offset < bom_len {
+        return app_info.offset..app_info.offset;
+    }
+    let span = char_span(&app_info.contents, app_info.encoding, app_info.offset);
+    if span.len() > 1 {
+        span
+    } else {
+        app_info.offset..app_info.offset
+    }
+}
+
+/// Decodes the bytes visible on the screen into rows, yielding exactly one character per byte.
+///
+/// NOTE: In UTF-8, a character takes up to 4 bytes and thus the encoding can break at the ends of the
+/// viewport. Decoding 3 more bytes at both ends and cropping them off afterwards solves the issue
+/// for the visible parts. The decoding always starts at an even offset so that UTF-16 code units
+/// stay aligned with the file.
+fn decode_visible(
+    app_info: &AppData,
+    bytes_per_line: usize,
+    lines_per_screen: usize,
+) -> Vec<Vec<RichChar>> {
+    let decode_start = app_info.start_address.saturating_sub(3) & !1;
+    let visible_end =
+        app_info.contents.len().min(app_info.start_address + bytes_per_line * lines_per_screen);
+    let decode_end = app_info.contents.len().min(visible_end + 3);
+    let bom_len = detect_bom(&app_info.contents).map_or(0, |(_, len)| len);
+
+    let decoder = app_info.encoding.decoder(app_info.contents.slice(decode_start..decode_end));
+    let mut decoder =
+        RowDecoder::new(decoder, bytes_per_line).skip_bytes(app_info.start_address - decode_start);
+    let mut rows = vec![vec![]];
+    loop {
+        let byte_pos = decode_start + decoder.byte_offset();
+        if byte_pos >= visible_end {
+            break;
+        }
+        match decoder.next() {
+            Some(RowItem::Char(character)) => {
+                let row = rows.last_mut().expect("there is always a row to push to");
+                // The byte order mark is not part of the text, so it's shown as control characters.
+                if byte_pos < bom_len {
+                    row.push(RichChar::new(char::from(character), Category::Control));
                 } else {
-                    format!("{byte:02X?}")
+                    row.push(character);
                 }
-                .chars()
-                .enumerate()
-                .map(|(nibble_pos, c)| {
-                    let byte_pos = app_info.start_address + (row * bytes_per_line) + col;
-                    let mut span =
-                        Span::styled(c.to_string(), Style::default().fg(*character.color()));
-                    let is_cursor = byte_pos == app_info.offset
-                        && ((nibble_pos == 0 && app_info.nibble == Nibble::Beginning)
-                            || (nibble_pos == 1 && app_info.nibble == Nibble::End));
-
-                    // Determine if the specified nibble (or space) should have a
-                    // lighter foreground because it is in the user's dragged range.
-                    // The logic is more complicated for hex because users can select
-                    // a single nibble from a byte.
-                    let mut in_drag = false;
-                    if let Some(drag) = app_info.last_drag {
-                        let drag_nibble = app_info.drag_nibble.unwrap_or(Nibble::End);
-                        if !(drag == app_info.offset && app_info.nibble == drag_nibble) {
-                            let mut start = drag;
-                            let mut end = app_info.offset;
-                            let mut start_nibble = drag_nibble;
-                            let mut end_nibble = app_info.nibble;
-
-                            if app_info.offset < drag {
-                                start = app_info.offset;
-                                end = drag;
-                                start_nibble = app_info.nibble;
-                                end_nibble = drag_nibble;
-                            }
+            }
+            Some(RowItem::Newline) => rows.push(vec![]),
+            None => break,
+        }
+    }
+    rows
+}
 
-                            // The only time the starting byte would not entirely be in
-                            // drag range is when the first nibble is not highlighted.
-                            // Similarly, the last nibble is only partially highlighted
-                            // when the second (and last) nibble is not selected.
-                            if byte_pos == start {
-                                in_drag = !(nibble_pos == 0 && start_nibble == Nibble::End);
-                            }
-                            if byte_pos == end {
-                                in_drag |= !(nibble_pos == 1 && end_nibble == Nibble::Beginning)
-                                    && nibble_pos != 2;
-                            }
-                            if start == end && nibble_pos == 2 {
-                                in_drag = false;
-                            } else if end - start > 1 {
-                                in_drag |= (start + 1..end).contains(&byte_pos);
+/// Display hex bytes with correct highlighting and colors by chunking the bytes into rows and
+/// formatting them into hex.
+fn generate_hex<'a>(
+    app_info: &AppData,
+    theme: &Theme,
+    rows: &[Vec<RichChar>],
+    cursor_char: &Range<usize>,
+    bytes_per_line: usize,
+) -> Vec<Spans<'a>> {
+    app_info.contents[app_info.start_address..]
+        .chunks(bytes_per_line)
+        .zip(rows)
+        .enumerate()
+        .map(|(row, (bytes, characters))| {
+            let spans = bytes
+                .iter()
+                .zip(characters)
+                .enumerate()
+                .flat_map(|(col, (&byte, character))| {
+                    // We don't want an extra space at the end of each row.
+                    if col < bytes_per_line - 1 {
+                        format!("{byte:02X?} ")
+                    } else {
+                        format!("{byte:02X?}")
+                    }
+                    .chars()
+                    .enumerate()
+                    .map(|(nibble_pos, c)| {
+                        let byte_pos = app_info.start_address + (row * bytes_per_line) + col;
+                        let mut span =
+                            Span::styled(c.to_string(), Style::default().fg(theme.hex(character)));
+                        // Bytes that differ from the compared file or were modified stand out in
+                        // their own colors.
+                        if nibble_pos != 2 && differs(app_info, byte_pos) {
+                            span.style = span.style.fg(COLOR_DIFF);
+                        }
+                        if nibble_pos != 2 && app_info.is_dirty(byte_pos) {
+                            span.style = span.style.fg(theme.hex_modified);
+                        }
+                        let is_cursor = byte_pos == app_info.offset
+                            && ((nibble_pos == 0 && app_info.nibble == Nibble::Beginning)
+                                || (nibble_pos == 1 && app_info.nibble == Nibble::End));
+
+                        // Determine if the specified nibble (or space) should have a
+                        // lighter foreground because it is in the user's dragged range.
+                        // The logic is more complicated for hex because users can select
+                        // a single nibble from a byte.
+                        let mut in_drag = false;
+                        if let Some(drag) = app_info.last_drag {
+                            let drag_nibble = app_info.drag_nibble.unwrap_or(Nibble::End);
+                            if !(drag == app_info.offset && app_info.nibble == drag_nibble) {
+                                let mut start = drag;
+                                let mut end = app_info.offset;
+                                let mut start_nibble = drag_nibble;
+                                let mut end_nibble = app_info.nibble;
+
+                                if app_info.offset < drag {
+                                    start = app_info.offset;
+                                    end = drag;
+                                    start_nibble = app_info.nibble;
+                                    end_nibble = drag_nibble;
+                                }
+
+                                // The only time the starting byte would not entirely be in
+                                // drag range is when the first nibble is not highlighted.
+                                // Similarly, the last nibble is only partially highlighted
+                                // when the second (and last) nibble is not selected.
+                                if byte_pos == start {
+                                    in_drag = !(nibble_pos == 0 && start_nibble == Nibble::End);
+                                }
+                                if byte_pos == end {
+                                    in_drag |= !(nibble_pos == 1
+                                        && end_nibble == Nibble::Beginning)
+                                        && nibble_pos != 2;
+                                }
+                                if start == end && nibble_pos == 2 {
+                                    in_drag = false;
+                                } else if end - start > 1 {
+                                    in_drag |= (start + 1..end).contains(&byte_pos);
+                                }
                             }
                         }
-                    }
-                    if is_cursor || in_drag {
-                        span.style = span.style.bg(COLOR_NULL);
-                    }
-                    span
+                        // The space after the last byte of a match is not part of it.
+                        let in_match = app_info.search_match.as_ref().map_or(false, |found| {
+                            found.contains(&byte_pos)
+                                && (nibble_pos != 2 || byte_pos + 1 < found.end)
+                        });
+                        // Like matches, the character at the cursor doesn't include the space after it.
+                        if cursor_char.contains(&byte_pos)
+                            && (nibble_pos != 2 || byte_pos + 1 < cursor_char.end)
+                        {
+                            span.style = span.style.add_modifier(Modifier::UNDERLINED);
+                        }
+                        if is_cursor {
+                            span.style = span.style.bg(theme.cursor);
+                        } else if in_drag {
+                            span.style = span.style.bg(theme.selection);
+                        } else if in_match {
+                            span.style = span.style.bg(COLOR_MATCH);
+                        }
+                        span
+                    })
+                    .collect::<Vec<Span>>()
                 })
-                .collect::<Vec<Span>>()
-            })
-            .collect::<Vec<Span>>();
-        Spans::from(spans)
-    })
-    .collect::<Vec<Spans>>()
+                .collect::<Vec<Span>>();
+            Spans::from(spans)
+        })
+        .collect::<Vec<Spans>>()
 }
 
 /// Display decoded bytes with correct highlighting and colors.
-///
-/// NOTE: In UTF-8, a character takes up to 4 bytes and thus the encoding can break at the ends of a
-/// chunk. Increasing the chunk size by 3 bytes at both ends before decoding and cropping them of
-/// afterwards solves the issue for the visible parts.
-fn generate_decoded(
+fn generate_decoded<'a>(
     app_info: &AppData,
+    theme: &Theme,
+    rows: &[Vec<RichChar>],
+    cursor_char: &Range<usize>,
     bytes_per_line: usize,
-    lines_per_screen: usize,
-) -> Vec<Spans> {
-    let initial_offset = app_info.start_address.min(3);
-    OverlappingChunks::new(
-        &app_info.contents[(app_info.start_address - initial_offset)..],
-        bytes_per_line,
-        6,
-    )
-    .take(lines_per_screen)
-    .enumerate()
-    .map(|(row, chunk)| {
-        Spans::from(
-            ByteAlignedDecoder::new(chunk, app_info.encoding)
-                .skip(initial_offset)
-                .take(bytes_per_line)
-                .enumerate()
-                .map(|(col, character)| {
-                    let byte_pos = app_info.start_address + (row * bytes_per_line) + col;
-                    let mut span = Span::styled(
-                        character.escape().to_string(),
-                        Style::default().fg(*character.color()),
-                    );
-                    // Highlight the selected byte in the ASCII table
-                    let last_drag = app_info.last_drag.unwrap_or(app_info.offset);
-                    if byte_pos == app_info.offset
-                        || (app_info.offset..=last_drag).contains(&byte_pos)
-                        || (last_drag..=app_info.offset).contains(&byte_pos)
-                    {
-                        span.style = span.style.bg(COLOR_NULL);
-                    }
-                    span
-                })
-                .collect::<Vec<Span>>(),
-        )
-    })
-    .collect::<Vec<Spans>>()
+) -> Vec<Spans<'a>> {
+    rows.iter()
+        .enumerate()
+        .map(|(row, characters)| {
+            Spans::from(
+                characters
+                    .iter()
+                    .enumerate()
+                    .map(|(col, character)| {
+                        let byte_pos = app_info.start_address + (row * bytes_per_line) + col;
+                        let mut span = Span::styled(
+                            character.escape().to_string(),
+                            Style::default().fg(theme.character(character)),
+                        );
+                        if differs(app_info, byte_pos) {
+                            span.style = span.style.fg(COLOR_DIFF);
+                        }
+                        if app_info.is_dirty(byte_pos) {
+                            span.style = span.style.fg(theme.hex_modified);
+                        }
+                        if cursor_char.contains(&byte_pos) {
+                            span.style = span.style.add_modifier(Modifier::UNDERLINED);
+                        }
+                        // Highlight the selected byte in the ASCII table
+                        let last_drag = app_info.last_drag.unwrap_or(app_info.offset);
+                        if byte_pos == app_info.offset {
+                            span.style = span.style.bg(theme.cursor);
+                        } else if (app_info.offset..=last_drag).contains(&byte_pos)
+                            || (last_drag..=app_info.offset).contains(&byte_pos)
+                        {
+                            span.style = span.style.bg(theme.selection);
+                        } else if app_info
+                            .search_match
+                            .as_ref()
+                            .map_or(false, |found| found.contains(&byte_pos))
+                        {
+                            span.style = span.style.bg(COLOR_MATCH);
+                        }
+                        span
+                    })
+                    .collect::<Vec<Span>>(),
+            )
+        })
+        .collect::<Vec<Spans>>()
 }
 
 /// Generates the dimensions of an x by y popup that is centered in Rect r.
@@ -454,3 +869,98 @@ fn popup_rect((x, y): (u16, u16), r: Rect) -> Rect {
         )
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_byte() {
+        let bytes = b"\nA\xC3\xA9\xFF";
+        assert_eq!(
+            describe_byte(bytes, 1, Encoding::Ascii).unwrap(),
+            "0x1 (1): 0x41 65 0b01000001 0o101 'A' Ascii"
+        );
+        assert_eq!(
+            describe_byte(bytes, 0, Encoding::Utf8).unwrap(),
+            "0x0 (0): 0x0A 10 0b00001010 0o12 '\\n' Control"
+        );
+        assert_eq!(
+            describe_byte(bytes, 4, Encoding::Utf8).unwrap(),
+            "0x4 (4): 0xFF 255 0b11111111 0o377 '�' Unknown"
+        );
+
+        // Both bytes of a multi-byte character describe it.
+        assert_eq!(
+            describe_byte(bytes, 2, Encoding::Utf8).unwrap(),
+            "0x2 (2): 0xC3 195 0b11000011 0o303 'é' Unicode"
+        );
+        assert_eq!(
+            describe_byte(bytes, 3, Encoding::Utf8).unwrap(),
+            "0x3 (3): 0xA9 169 0b10101001 0o251 'é' Unicode"
+        );
+        assert_eq!(describe_byte(bytes, 5, Encoding::Utf8), None);
+    }
+
+    #[test]
+    fn test_required_width() {
+        // The address table, 16 bytes of hex, and 16 characters, each with borders.
+        assert_eq!(required_width(16, 8), 10 + (16 * 3 - 1 + 2) + (16 + 2));
+        assert_eq!(required_width(1, 8), 10 + 4 + 3);
+        assert_eq!(required_width(24, 8), 109);
+        assert_eq!(required_width(usize::MAX, 8), u16::MAX);
+    }
+
+    #[test]
+    fn test_fit_bytes_per_row() {
+        // The widest layout that fits is picked automatically.
+        assert_eq!(fit_bytes_per_row(None, 109, 8), 24);
+        assert_eq!(fit_bytes_per_row(None, 112, 8), 24);
+        assert_eq!(fit_bytes_per_row(None, 113, 8), 25);
+        for width in 50..300 {
+            let fitted = fit_bytes_per_row(None, width, 8);
+            assert!(required_width(fitted, 8) <= width);
+            assert!(required_width(fitted + 1, 8) > width);
+        }
+
+        // A fixed number of bytes is kept if it fits, including ones that aren't powers of two.
+        assert_eq!(fit_bytes_per_row(Some(16), 200, 8), 16);
+        assert_eq!(fit_bytes_per_row(Some(24), 109, 8), 24);
+        assert_eq!(fit_bytes_per_row(Some(13), 200, 8), 13);
+        assert_eq!(fit_bytes_per_row(Some(32), 109, 8), 24);
+
+        // At least one byte is shown, however narrow the terminal is.
+        assert_eq!(fit_bytes_per_row(None, 0, 8), 1);
+        assert_eq!(fit_bytes_per_row(Some(0), 200, 8), 1);
+
+        // Wider addresses leave less room for the editors.
+        assert_eq!(fit_bytes_per_row(None, 113, 12), 24);
+        assert_eq!(fit_bytes_per_row(Some(25), 113, 12), 24);
+    }
+
+    #[test]
+    fn test_format_address() {
+        assert_eq!(format_address(0x1F, AddressBase::Hex, 8), "0000001F");
+        assert_eq!(format_address(31, AddressBase::Decimal, 8), "00000031");
+        assert_eq!(format_address(31, AddressBase::Octal, 8), "00000037");
+        assert_eq!(format_address(0xAB_CDEF, AddressBase::Hex, 4), "ABCDEF");
+        assert_eq!(format_address(0, AddressBase::Octal, 1), "0");
+    }
+
+    #[test]
+    fn test_address_digits() {
+        // Small files keep the default width in every base.
+        assert_eq!(address_digits(0, AddressBase::Hex), 8);
+        assert_eq!(address_digits(1000, AddressBase::Decimal), 8);
+        assert_eq!(address_digits(1000, AddressBase::Octal), 8);
+
+        // The address after the last byte needs to fit as well.
+        assert_eq!(address_digits(0xFFFF_FFFF, AddressBase::Hex), 8);
+        assert_eq!(address_digits(0x1_0000_0000, AddressBase::Hex), 9);
+        assert_eq!(address_digits(99_999_999, AddressBase::Decimal), 8);
+        assert_eq!(address_digits(100_000_000, AddressBase::Decimal), 9);
+        assert_eq!(address_digits(0o7777_7777, AddressBase::Octal), 8);
+        assert_eq!(address_digits(0o1_0000_0000, AddressBase::Octal), 9);
+        assert_eq!(address_digits(usize::MAX, AddressBase::Decimal), 20);
+    }
+}
@@ -0,0 +1,285 @@
+//! Finding byte patterns in the file contents.
+//!
+//! Searches wrap around at the ends of the contents, so a match is found regardless of where the
+//! cursor is.
+
+use std::ops::Range;
+
+use crate::{
+    character::is_printable,
+    decoder::{Decoder, Encoding},
+};
+
+/// The number of bytes that are decoded at once when searching backwards for non-printable
+/// characters, as decoders can't start from the end.
+const NONPRINTABLE_CHUNK: usize = 4096;
+
+/// What the user searched for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Query {
+    /// An exact sequence of bytes, entered as hexadecimal.
+    Bytes(Vec<u8>),
+    /// A string that is encoded with the active encoding, whose ASCII letters are matched
+    /// case-insensitively.
+    Text(String),
+}
+
+impl Query {
+    /// Parses the input of the search popup.
+    ///
+    /// The input is parsed as hexadecimal bytes if it is preceded with "0x", where any whitespace
+    /// between the bytes is ignored (i.e. "0xDE AD BE EF"). Otherwise it is searched for as text.
+    pub(crate) fn parse(input: &str) -> Result<Self, String> {
+        if let Some(hex) = input.strip_prefix("0x") {
+            let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace()).collect();
+            if digits.is_empty() || digits.len() % 2 != 0 {
+                return Err(String::from("Hex needs full bytes!"));
+            }
+            digits
+                .chunks(2)
+                .map(|pair| {
+                    let pair: String = pair.iter().collect();
+                    u8::from_str_radix(&pair, 16).map_err(|_| format!("Invalid Hex: {pair}"))
+                })
+                .collect::<Result<_, _>>()
+                .map(Query::Bytes)
+        } else if input.is_empty() {
+            Err(String::from("Nothing to search for!"))
+        } else {
+            Ok(Query::Text(input.to_string()))
+        }
+    }
+}
+
+/// A sequence of bytes to search for, where every position matches either of two bytes.
+///
+/// This allows text to be matched regardless of case even in encodings that are not ASCII
+/// compatible, by pairing the encoding of each lowercase letter with that of its uppercase one.
+pub(crate) type Pattern = Vec<(u8, u8)>;
+
+/// A pattern that only matches `bytes` exactly.
+pub(crate) fn exact(bytes: &[u8]) -> Pattern {
+    bytes.iter().map(|&byte| (byte, byte)).collect()
+}
+
+/// A pattern that matches `text` in the given encoding, ignoring the case of ASCII letters.
+/// Returns `None` if a character of the text cannot be represented in the encoding.
+pub(crate) fn encode_text(text: &str, encoding: Encoding) -> Option<Pattern> {
+    let mut pattern = Pattern::new();
+    for character in text.chars() {
+        let lower = encoding.encode_char(character.to_ascii_lowercase());
+        let upper = encoding.encode_char(character.to_ascii_uppercase());
+        match (lower, upper) {
+            (Some(lower), Some(upper)) if lower.len() == upper.len() => {
+                pattern.extend(lower.into_iter().zip(upper));
+            }
+            _ => pattern.extend(exact(&encoding.encode_char(character)?)),
+        }
+    }
+    Some(pattern)
+}
+
+fn matches_at(haystack: &[u8], pattern: &[(u8, u8)], offset: usize) -> bool {
+    haystack[offset..offset + pattern.len()]
+        .iter()
+        .zip(pattern)
+        .all(|(&byte, &(first, second))| byte == first || byte == second)
+}
+
+/// Finds the first match of `pattern` at or after `start`, continuing from the beginning of
+/// `haystack` if there is none.
+pub(crate) fn find(haystack: &[u8], pattern: &[(u8, u8)], start: usize) -> Option<usize> {
+    if pattern.is_empty() || pattern.len() > haystack.len() {
+        return None;
+    }
+    let last = haystack.len() - pattern.len();
+    let start = start.min(last + 1);
+    (start..=last).chain(0..start).find(|&offset| matches_at(haystack, pattern, offset))
+}
+
+/// Finds the last match of `pattern` before `start`, continuing from the end of `haystack` if
+/// there is none.
+pub(crate) fn rfind(haystack: &[u8], pattern: &[(u8, u8)], start: usize) -> Option<usize> {
+    if pattern.is_empty() || pattern.len() > haystack.len() {
+        return None;
+    }
+    let last = haystack.len() - pattern.len();
+    let start = start.min(last + 1);
+    (0..start)
+        .rev()
+        .chain((start..=last).rev())
+        .find(|&offset| matches_at(haystack, pattern, offset))
+}
+
+/// The offsets of the characters starting in `range` that `encoding` decodes as control characters
+/// or can't decode at all, in ascending order.
+///
+/// Like on the screen, the decoding starts a few bytes early and ends a few bytes late, so that
+/// the characters at the edges of `range` are decoded from all of their bytes.
+fn nonprintable_in(
+    contents: &[u8],
+    encoding: Encoding,
+    range: Range<usize>,
+) -> impl Iterator<Item = usize> + '_ {
+    let decode_start = range.start.saturating_sub(3) & !1;
+    let decode_end = contents.len().min(range.end + 3);
+    encoding
+        .decoder(&contents[decode_start..decode_end])
+        .ranged()
+        .filter(|(character, typ, _)| !is_printable(*character, typ))
+        .map(move |(_, _, bytes)| decode_start + bytes.start)
+        .filter(move |offset| range.contains(offset))
+}
+
+/// Like [`nonprintable_in`], but only finds the last one and decodes backwards in chunks, so that
+/// a match right before the end of `range` is found quickly.
+fn last_nonprintable_in(contents: &[u8], encoding: Encoding, range: Range<usize>) -> Option<usize> {
+    let mut end = range.end;
+    while end > range.start {
+        let chunk_start = end.saturating_sub(NONPRINTABLE_CHUNK).max(range.start);
+        if let Some(found) = nonprintable_in(contents, encoding, chunk_start..end).last() {
+            return Some(found);
+        }
+        end = chunk_start;
+    }
+    None
+}
+
+/// Finds the first character at or after `start` that `encoding` decodes as a control character
+/// or can't decode, continuing from the beginning of `contents` if there is none.
+pub(crate) fn find_nonprintable(
+    contents: &[u8],
+    encoding: Encoding,
+    start: usize,
+) -> Option<usize> {
+    let start = start.min(contents.len());
+    nonprintable_in(contents, encoding, start..contents.len())
+        .next()
+        .or_else(|| nonprintable_in(contents, encoding, 0..start).next())
+}
+
+/// Like [`find_nonprintable`], but finds the last one before `start`, continuing from the end of
+/// `contents` if there is none.
+pub(crate) fn rfind_nonprintable(
+    contents: &[u8],
+    encoding: Encoding,
+    start: usize,
+) -> Option<usize> {
+    let start = start.min(contents.len());
+    last_nonprintable_in(contents, encoding, 0..start)
+        .or_else(|| last_nonprintable_in(contents, encoding, start..contents.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAYSTACK: &[u8] = b"\xDE\xAD\xBE\xEF Hello hello \xDE\xAD\xBE\xEF";
+
+    fn text(text: &str) -> Pattern {
+        encode_text(text, Encoding::Ascii).unwrap()
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(HAYSTACK, &exact(b"\xDE\xAD\xBE\xEF"), 0), Some(0));
+        assert_eq!(find(HAYSTACK, &exact(b"\xDE\xAD\xBE\xEF"), 1), Some(17));
+        assert_eq!(find(HAYSTACK, &exact(b"hello"), 0), Some(11));
+        assert_eq!(find(HAYSTACK, &text("hello"), 0), Some(5));
+    }
+
+    #[test]
+    fn test_find_not_found() {
+        assert_eq!(find(HAYSTACK, &exact(b"\xDE\xAD\xBE\xEE"), 0), None);
+        assert_eq!(find(HAYSTACK, &text("world"), 0), None);
+        assert_eq!(find(HAYSTACK, &exact(b""), 0), None);
+        assert_eq!(find(b"ab", &exact(b"abc"), 0), None);
+        assert_eq!(rfind(HAYSTACK, &text("world"), 10), None);
+    }
+
+    #[test]
+    fn test_find_wrap_around() {
+        assert_eq!(find(HAYSTACK, &exact(b"\xDE\xAD\xBE\xEF"), 18), Some(0));
+        assert_eq!(find(HAYSTACK, &text("hello"), 12), Some(5));
+        assert_eq!(find(HAYSTACK, &text("hello"), usize::MAX), Some(5));
+        assert_eq!(rfind(HAYSTACK, &exact(b"\xDE\xAD\xBE\xEF"), 17), Some(0));
+        assert_eq!(rfind(HAYSTACK, &exact(b"\xDE\xAD\xBE\xEF"), 0), Some(17));
+        assert_eq!(rfind(HAYSTACK, &text("hello"), 5), Some(11));
+    }
+
+    #[test]
+    fn test_find_text() {
+        let ascii = b"..OK..";
+        assert_eq!(find(ascii, &encode_text("ok", Encoding::Ascii).unwrap(), 0), Some(2));
+
+        let utf8 = "caf\u{E9} CAF\u{C9}".as_bytes();
+        assert_eq!(find(utf8, &encode_text("café", Encoding::Utf8).unwrap(), 0), Some(0));
+        assert_eq!(find(utf8, &encode_text("CAFÉ", Encoding::Utf8).unwrap(), 1), Some(6));
+
+        let utf16 = b"\x00\x00O\x00K\x00";
+        let pattern = encode_text("OK", Encoding::Utf16Le).unwrap();
+        assert_eq!(pattern, [(0x6F, 0x4F), (0x00, 0x00), (0x6B, 0x4B), (0x00, 0x00)]);
+        assert_eq!(find(utf16, &pattern, 0), Some(2));
+        assert_eq!(find(utf16, &encode_text("ok", Encoding::Utf16Le).unwrap(), 0), Some(2));
+        assert_eq!(find(utf16, &encode_text("KO", Encoding::Utf16Le).unwrap(), 0), None);
+
+        assert_eq!(encode_text("é", Encoding::Ascii), None);
+    }
+
+    #[test]
+    fn test_query_parse() {
+        assert_eq!(Query::parse("0xDE AD be ef"), Ok(Query::Bytes(vec![0xDE, 0xAD, 0xBE, 0xEF])));
+        assert_eq!(Query::parse("Hello"), Ok(Query::Text(String::from("Hello"))));
+        assert!(Query::parse("0xDEA").is_err());
+        assert!(Query::parse("0xZZ").is_err());
+        assert!(Query::parse("0x").is_err());
+        assert!(Query::parse("").is_err());
+    }
+
+    #[test]
+    fn test_find_nonprintable() {
+        let ascii = b"ab\x01cd\xFFef\ngh";
+        assert_eq!(find_nonprintable(ascii, Encoding::Ascii, 0), Some(2));
+        assert_eq!(find_nonprintable(ascii, Encoding::Ascii, 3), Some(5));
+        assert_eq!(find_nonprintable(ascii, Encoding::Ascii, 6), Some(8));
+        assert_eq!(find_nonprintable(ascii, Encoding::Ascii, 9), Some(2));
+        assert_eq!(rfind_nonprintable(ascii, Encoding::Ascii, 8), Some(5));
+        assert_eq!(rfind_nonprintable(ascii, Encoding::Ascii, 2), Some(8));
+        assert_eq!(find_nonprintable(b"plain text", Encoding::Ascii, 0), None);
+        assert_eq!(rfind_nonprintable(b"plain text", Encoding::Ascii, 5), None);
+
+        // Multibyte characters are printable, and a search starting inside one skips it.
+        let utf8 = "caf\u{E9} \u{1F600}!".as_bytes();
+        assert_eq!(find_nonprintable(utf8, Encoding::Utf8, 0), None);
+        assert_eq!(find_nonprintable(utf8, Encoding::Utf8, 7), None);
+        let utf8 = b"\xC3\xA9\xC3!\x80\xE9";
+        assert_eq!(find_nonprintable(utf8, Encoding::Utf8, 0), Some(2));
+        assert_eq!(find_nonprintable(utf8, Encoding::Utf8, 3), Some(4));
+        assert_eq!(rfind_nonprintable(utf8, Encoding::Utf8, 6), Some(5));
+        assert_eq!(rfind_nonprintable(utf8, Encoding::Utf8, 2), Some(5));
+
+        // UTF-16 decodes control characters like any other.
+        let utf16 = b"a\x00\n\x00b\x00";
+        assert_eq!(find_nonprintable(utf16, Encoding::Utf16Le, 0), Some(2));
+    }
+
+    #[test]
+    fn test_find_nonprintable_far_away() {
+        let mut contents = vec![b'a'; 3 * NONPRINTABLE_CHUNK + 5];
+        contents[10] = 0x00;
+        contents[NONPRINTABLE_CHUNK] = 0x7F;
+        contents[2 * NONPRINTABLE_CHUNK + 1] = 0xFF;
+        let len = contents.len();
+        assert_eq!(
+            rfind_nonprintable(&contents, Encoding::Ascii, len),
+            Some(2 * NONPRINTABLE_CHUNK + 1)
+        );
+        assert_eq!(
+            rfind_nonprintable(&contents, Encoding::Ascii, 2 * NONPRINTABLE_CHUNK),
+            Some(NONPRINTABLE_CHUNK)
+        );
+        assert_eq!(rfind_nonprintable(&contents, Encoding::Ascii, NONPRINTABLE_CHUNK), Some(10));
+        assert_eq!(find_nonprintable(&contents, Encoding::Ascii, 11), Some(NONPRINTABLE_CHUNK));
+        assert_eq!(find_nonprintable(&contents, Encoding::Ascii, len), Some(10));
+    }
+}
@@ -0,0 +1,88 @@
+//! Finding runs of printable text in binary data, like the `strings` utility.
+
+use crate::{
+    character::is_printable,
+    decoder::{Decoder, Encoding},
+};
+
+/// The number of characters a run needs at least to be listed by default.
+pub(crate) const DEFAULT_MIN_LEN: usize = 4;
+
+/// Finds the runs of at least `min_len` consecutive printable characters in `bytes`, as decoded
+/// with `encoding`, along with the offsets they start at.
+///
+/// Control characters and bytes that can't be decoded end a run, so tabs and newlines split
+/// strings as well. As the decoders are reused, UTF-8 and UTF-16 text is found like ASCII is.
+pub(crate) fn find_strings(
+    bytes: &[u8],
+    encoding: Encoding,
+    min_len: usize,
+) -> Vec<(usize, String)> {
+    let mut strings = vec![];
+    let mut run: Option<(usize, String, usize)> = None;
+    for (character, typ, range) in encoding.decoder(bytes).ranged() {
+        if !is_printable(character, &typ) {
+            if let Some((offset, string, len)) = run.take() {
+                if len >= min_len {
+                    strings.push((offset, string));
+                }
+            }
+        } else if let Some((_, string, len)) = &mut run {
+            string.push(character);
+            *len += 1;
+        } else {
+            run = Some((range.start, String::from(character), 1));
+        }
+    }
+    if let Some((offset, string, len)) = run {
+        if len >= min_len {
+            strings.push((offset, string));
+        }
+    }
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_len() {
+        let bytes = b"\x00abc\x01abcd\xFFabcdefg\nab";
+        assert_eq!(
+            find_strings(bytes, Encoding::Ascii, 4),
+            vec![(5, String::from("abcd")), (10, String::from("abcdefg"))]
+        );
+        assert_eq!(find_strings(bytes, Encoding::Ascii, 5), vec![(10, String::from("abcdefg"))]);
+        assert_eq!(find_strings(bytes, Encoding::Ascii, 2).len(), 4);
+        assert_eq!(find_strings(bytes, Encoding::Ascii, 8), vec![]);
+
+        // A run at the very end counts as well.
+        assert_eq!(
+            find_strings(b"\x00\x00tail", Encoding::Ascii, 4),
+            vec![(2, String::from("tail"))]
+        );
+    }
+
+    #[test]
+    fn test_utf8() {
+        // The length is counted in characters rather than bytes.
+        let bytes = "\u{0}caf\u{E9}\u{0}d\u{E9}j\u{0}".as_bytes();
+        assert_eq!(find_strings(bytes, Encoding::Utf8, 4), vec![(1, String::from("café"))]);
+        assert_eq!(find_strings(bytes, Encoding::Utf8, 3).len(), 2);
+    }
+
+    #[test]
+    fn test_utf16le() {
+        let mut bytes = vec![0x00, 0xD8, 0x00, 0x00];
+        bytes.extend("Hello, w\u{F6}rld".encode_utf16().flat_map(u16::to_le_bytes));
+        bytes.extend([0x00, 0x00, b'h', 0x00, b'i', 0x00]);
+        assert_eq!(
+            find_strings(&bytes, Encoding::Utf16Le, 4),
+            vec![(4, String::from("Hello, w\u{F6}rld"))]
+        );
+
+        // The same bytes as ASCII are only single characters between the zeros.
+        assert_eq!(find_strings(&bytes, Encoding::Ascii, 4), vec![]);
+    }
+}
@@ -0,0 +1,182 @@
+//! Switching between several open files.
+
+use std::{mem, path::Path};
+
+/// The open files, of which one is active at a time.
+///
+/// The state of the active file is owned by the caller, so that it can be used without going
+/// through the tabs. It is passed to the methods that change the active tab, which swap it with
+/// the state of the tab that becomes active. The others are parked here until then.
+pub(crate) struct Tabs<T> {
+    /// The state of each tab, in the order they're shown. The slot of the active one is empty.
+    parked: Vec<Option<T>>,
+
+    /// The index of the active tab.
+    active: usize,
+}
+
+impl<T> Default for Tabs<T> {
+    /// A single tab, which is active.
+    fn default() -> Self {
+        Self { parked: vec![None], active: 0 }
+    }
+}
+
+impl<T> Tabs<T> {
+    pub(crate) fn len(&self) -> usize {
+        self.parked.len()
+    }
+
+    /// The index of the active tab.
+    pub(crate) fn active(&self) -> usize {
+        self.active
+    }
+
+    /// The state of every tab in order, which is `active` for the active one.
+    pub(crate) fn iter<'a>(&'a self, active: &'a T) -> impl Iterator<Item = &'a T> {
+        self.parked.iter().map(move |parked| parked.as_ref().unwrap_or(active))
+    }
+
+    /// The states of the tabs that aren't active.
+    pub(crate) fn parked(&self) -> impl Iterator<Item = &T> {
+        self.parked.iter().flatten()
+    }
+
+    /// Adds a tab with `opened` right after the active one and switches to it.
+    pub(crate) fn open(&mut self, active: &mut T, opened: T) {
+        self.parked[self.active] = Some(mem::replace(active, opened));
+        self.active += 1;
+        self.parked.insert(self.active, None);
+    }
+
+    /// Switches to the tab at `index`, which must exist.
+    pub(crate) fn switch_to(&mut self, active: &mut T, index: usize) {
+        if index == self.active {
+            return;
+        }
+        let next = self.parked[index].take().expect("only the active tab isn't parked");
+        self.parked[self.active] = Some(mem::replace(active, next));
+        self.active = index;
+    }
+
+    /// Switches to the next tab, wrapping around to the first one.
+    pub(crate) fn next(&mut self, active: &mut T) {
+        self.switch_to(active, (self.active + 1) % self.len());
+    }
+
+    /// Switches to the previous tab, wrapping around to the last one.
+    pub(crate) fn prev(&mut self, active: &mut T) {
+        self.switch_to(active, (self.active + self.len() - 1) % self.len());
+    }
+
+    /// Closes the active tab and switches to the one after it, or the one before if it was the
+    /// last. Returns the state of the closed tab, or `None` if it's the only one, which can't be
+    /// closed.
+    pub(crate) fn close(&mut self, active: &mut T) -> Option<T> {
+        if self.len() == 1 {
+            return None;
+        }
+        let closed = self.active;
+        let next = if closed + 1 < self.len() { closed + 1 } else { closed - 1 };
+        let next_state = self.parked[next].take().expect("only the active tab isn't parked");
+        self.parked.remove(closed);
+        self.active = next.min(closed);
+        Some(mem::replace(active, next_state))
+    }
+}
+
+/// The name of the file at `path` to show in its tab.
+pub(crate) fn tab_name(path: &Path) -> String {
+    path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The tabs in order, with brackets around the active one.
+    fn names(tabs: &Tabs<&str>, active: &&str) -> Vec<String> {
+        tabs.iter(active)
+            .enumerate()
+            .map(
+                |(i, name)| {
+                    if i == tabs.active() {
+                        format!("[{name}]")
+                    } else {
+                        (*name).to_string()
+                    }
+                },
+            )
+            .collect()
+    }
+
+    #[test]
+    fn test_open() {
+        let mut tabs = Tabs::default();
+        let mut active = "a";
+        tabs.open(&mut active, "b");
+        assert_eq!((tabs.len(), tabs.active(), active), (2, 1, "b"));
+        tabs.switch_to(&mut active, 0);
+        tabs.open(&mut active, "c");
+        assert_eq!(names(&tabs, &active), ["a", "[c]", "b"]);
+        assert_eq!(tabs.parked().copied().collect::<Vec<_>>(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_switch() {
+        let mut tabs = Tabs::default();
+        let mut active = "a";
+        tabs.open(&mut active, "b");
+        tabs.open(&mut active, "c");
+        tabs.switch_to(&mut active, 0);
+        assert_eq!(names(&tabs, &active), ["[a]", "b", "c"]);
+        tabs.switch_to(&mut active, 0);
+        assert_eq!(names(&tabs, &active), ["[a]", "b", "c"]);
+
+        // Both directions wrap around.
+        tabs.prev(&mut active);
+        assert_eq!((tabs.active(), active), (2, "c"));
+        tabs.next(&mut active);
+        assert_eq!((tabs.active(), active), (0, "a"));
+        tabs.next(&mut active);
+        assert_eq!(names(&tabs, &active), ["a", "[b]", "c"]);
+
+        // A single tab stays active.
+        let mut single = Tabs::default();
+        let mut only = "a";
+        single.next(&mut only);
+        single.prev(&mut only);
+        assert_eq!((single.active(), only), (0, "a"));
+    }
+
+    #[test]
+    fn test_close() {
+        let mut tabs = Tabs::default();
+        let mut active = "a";
+        tabs.open(&mut active, "b");
+        tabs.open(&mut active, "c");
+        tabs.open(&mut active, "d");
+        tabs.switch_to(&mut active, 1);
+
+        // The tab after the closed one becomes active, unless it was the last.
+        assert_eq!(tabs.close(&mut active), Some("b"));
+        assert_eq!(names(&tabs, &active), ["a", "[c]", "d"]);
+        tabs.switch_to(&mut active, 2);
+        assert_eq!(tabs.close(&mut active), Some("d"));
+        assert_eq!(names(&tabs, &active), ["a", "[c]"]);
+        tabs.switch_to(&mut active, 0);
+        assert_eq!(tabs.close(&mut active), Some("a"));
+        assert_eq!(names(&tabs, &active), ["[c]"]);
+
+        // The last tab can't be closed.
+        assert_eq!(tabs.close(&mut active), None);
+        assert_eq!((tabs.len(), tabs.active(), active), (1, 0, "c"));
+    }
+
+    #[test]
+    fn test_tab_name() {
+        assert_eq!(tab_name(Path::new("/tmp/disk.img")), "disk.img");
+        assert_eq!(tab_name(Path::new("firmware.bin")), "firmware.bin");
+        assert_eq!(tab_name(Path::new("/")), "/");
+    }
+}
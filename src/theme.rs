@@ -0,0 +1,241 @@
+//! The colors that the file contents are shown in, which can be changed in a theme file.
+//!
+//! A theme file is a TOML file of `key = "color"` pairs, one for each color to change:
+//!
+//! ```toml
+//! # Modified bytes in light red, selections on a dark blue background.
+//! hex_modified = "light_red"
+//! selection = "#1f3a5f"
+//! bookmark = 207
+//! ```
+//!
+//! Colors are either named (`"black"`, `"dark_gray"`, `"light_cyan"`, ..., or `"reset"` for the
+//! color of the terminal), given as `"#rrggbb"`, or an index into the 256 colors of the terminal.
+//! Only this subset of TOML is understood, as there are no tables or other values in a theme.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use tui::style::Color;
+
+use crate::character::{Category, RichChar};
+
+/// The colors of the address table and the editors.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Theme {
+    /// The addresses of the rows.
+    pub(crate) offset: Color,
+    /// The hex bytes, which are colored like their characters in the text pane if this is `None`.
+    pub(crate) hex_normal: Option<Color>,
+    /// The bytes that were modified since the file was opened or saved, in both editors.
+    pub(crate) hex_modified: Color,
+    /// Printable ASCII characters.
+    pub(crate) ascii_printable: Color,
+    /// Control characters.
+    pub(crate) ascii_control: Color,
+    /// Bytes that couldn't be decoded.
+    pub(crate) ascii_unknown: Color,
+    /// The background of the selected bytes.
+    pub(crate) selection: Color,
+    /// The background of the byte at the cursor.
+    pub(crate) cursor: Color,
+    /// The addresses of the rows that contain a bookmark.
+    pub(crate) bookmark: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            offset: Color::Reset,
+            hex_normal: None,
+            hex_modified: Color::LightYellow,
+            ascii_printable: *Category::Ascii.color(),
+            ascii_control: *Category::Control.color(),
+            ascii_unknown: *Category::Unknown.color(),
+            selection: Color::DarkGray,
+            cursor: Color::DarkGray,
+            bookmark: Color::Magenta,
+        }
+    }
+}
+
+impl Theme {
+    /// The color `character` is shown in.
+    pub(crate) fn character(&self, character: &RichChar) -> Color {
+        match character.category() {
+            Category::Ascii => self.ascii_printable,
+            Category::Control => self.ascii_control,
+            Category::Unknown => self.ascii_unknown,
+            category => *category.color(),
+        }
+    }
+
+    /// The color the hex digits of a byte are shown in, which is `character` decoded from it.
+    pub(crate) fn hex(&self, character: &RichChar) -> Color {
+        self.hex_normal.unwrap_or_else(|| self.character(character))
+    }
+}
+
+/// The reasons a theme file can't be used, along with the line they occurred on.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ThemeError {
+    /// A line is neither a `key = value` pair, a comment, nor empty.
+    Syntax(usize),
+    /// A key isn't the name of a color in the theme.
+    UnknownKey(usize, String),
+    /// A value isn't a color.
+    InvalidColor(usize, String),
+}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Syntax(line) => write!(f, "line {line}: expected key = \"color\""),
+            ThemeError::UnknownKey(line, key) => write!(f, "line {line}: unknown key {key}"),
+            ThemeError::InvalidColor(line, value) => {
+                write!(f, "line {line}: invalid color {value}")
+            }
+        }
+    }
+}
+
+impl Error for ThemeError {}
+
+/// Parses the theme file `toml`. The colors it doesn't set are left as in the default theme.
+pub(crate) fn parse_theme(toml: &str) -> Result<Theme, ThemeError> {
+    let mut theme = Theme::default();
+    for (i, line) in toml.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or(ThemeError::Syntax(line_number))?;
+        let (key, value) = (key.trim(), parse_value(value.trim(), line_number)?);
+        let color = parse_color(value)
+            .ok_or_else(|| ThemeError::InvalidColor(line_number, value.into()))?;
+        match key {
+            "offset" => theme.offset = color,
+            "hex_normal" => theme.hex_normal = Some(color),
+            "hex_modified" => theme.hex_modified = color,
+            "ascii_printable" => theme.ascii_printable = color,
+            "ascii_control" => theme.ascii_control = color,
+            "ascii_unknown" => theme.ascii_unknown = color,
+            "selection" => theme.selection = color,
+            "cursor" => theme.cursor = color,
+            "bookmark" => theme.bookmark = color,
+            _ => return Err(ThemeError::UnknownKey(line_number, key.into())),
+        }
+    }
+    Ok(theme)
+}
+
+/// Strips the quotes and any trailing comment from `value`. Integers aren't quoted.
+fn parse_value(value: &str, line_number: usize) -> Result<&str, ThemeError> {
+    let (value, rest) = match value.strip_prefix('"') {
+        Some(quoted) => quoted.split_once('"').ok_or(ThemeError::Syntax(line_number))?,
+        None => value.split_once('#').map_or((value, ""), |(value, _)| (value.trim_end(), "")),
+    };
+    let rest = rest.trim_start();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(ThemeError::Syntax(line_number));
+    }
+    Ok(value)
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    if let Ok(index) = value.parse::<u8>() {
+        return Some(Color::Indexed(index));
+    }
+    let color = match value.to_ascii_lowercase().as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "dark_gray" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_theme() {
+        let theme = parse_theme(
+            "# Only some colors are changed.\n\
+             \n\
+             hex_modified = \"light_red\"\n\
+             selection = \"#1f3a5f\"  # a dark blue\n\
+             bookmark = 207\n",
+        )
+        .unwrap();
+        assert_eq!(
+            theme,
+            Theme {
+                hex_modified: Color::LightRed,
+                selection: Color::Rgb(0x1F, 0x3A, 0x5F),
+                bookmark: Color::Indexed(207),
+                ..Theme::default()
+            }
+        );
+        assert_eq!(parse_theme(""), Ok(Theme::default()));
+    }
+
+    #[test]
+    fn test_hex_normal() {
+        let ascii = RichChar::new('a', Category::Ascii);
+        let theme = Theme::default();
+        assert_eq!(theme.hex(&ascii), theme.ascii_printable);
+        let theme = parse_theme("hex_normal = \"white\"").unwrap();
+        assert_eq!(theme.hex(&ascii), Color::White);
+        assert_eq!(theme.character(&ascii), Theme::default().ascii_printable);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_theme("cursor"), Err(ThemeError::Syntax(1)));
+        assert_eq!(parse_theme("\n[colors]"), Err(ThemeError::Syntax(2)));
+        assert_eq!(parse_theme("cursor = \"red"), Err(ThemeError::Syntax(1)));
+        assert_eq!(parse_theme("cursor = \"red\" blue"), Err(ThemeError::Syntax(1)));
+        assert_eq!(
+            parse_theme("cursr = \"red\""),
+            Err(ThemeError::UnknownKey(1, String::from("cursr")))
+        );
+        assert_eq!(
+            parse_theme("cursor = \"purple\""),
+            Err(ThemeError::InvalidColor(1, String::from("purple")))
+        );
+        assert_eq!(
+            parse_theme("cursor = \"#12345\""),
+            Err(ThemeError::InvalidColor(1, String::from("#12345")))
+        );
+        assert_eq!(
+            parse_theme("cursor = 256"),
+            Err(ThemeError::InvalidColor(1, String::from("256")))
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Noticing when the file under editing is changed on disk by another process.
+//!
+//! Files are watched by polling their size and modification time while heh waits for input, which
+//! needs no support from the operating system. This is only done with the `watch` feature. Without
+//! it, no file is ever watched.
+
+use std::{path::Path, time::Duration};
+
+#[cfg(feature = "watch")]
+use std::{fs, path::PathBuf, time::SystemTime};
+
+/// How long heh waits for input before checking the watched file again.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The size and modification time of a file, which change whenever it is written to.
+#[cfg(feature = "watch")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+#[cfg(feature = "watch")]
+impl Stamp {
+    /// The stamp of the file at `path`, or `None` if it doesn't exist (anymore).
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self { len: metadata.len(), modified: metadata.modified().ok() })
+    }
+}
+
+/// A file on disk that is checked for changes.
+#[cfg(feature = "watch")]
+pub(crate) struct FileWatch {
+    path: PathBuf,
+    stamp: Option<Stamp>,
+}
+
+#[cfg(feature = "watch")]
+impl FileWatch {
+    /// Watches the file at `path`, which is unchanged as of now.
+    // This can't fail, but without the `watch` feature it always does.
+    #[allow(clippy::unnecessary_wraps)]
+    pub(crate) fn new(path: &Path) -> Option<Self> {
+        Some(Self { path: path.to_owned(), stamp: Stamp::of(path) })
+    }
+
+    /// Whether the file changed since this was last called or the file was marked as unchanged.
+    /// Each change is only reported once.
+    pub(crate) fn changed(&mut self) -> bool {
+        let stamp = Stamp::of(&self.path);
+        let changed = stamp != self.stamp;
+        self.stamp = stamp;
+        changed
+    }
+
+    /// Marks the file as unchanged, as heh just read or wrote it itself.
+    pub(crate) fn refresh(&mut self) {
+        self.stamp = Stamp::of(&self.path);
+    }
+}
+
+/// A file on disk that is checked for changes, which can't be created as heh is built without
+/// watching.
+#[cfg(not(feature = "watch"))]
+pub(crate) enum FileWatch {}
+
+#[cfg(not(feature = "watch"))]
+impl FileWatch {
+    pub(crate) fn new(_: &Path) -> Option<Self> {
+        None
+    }
+
+    pub(crate) fn changed(&mut self) -> bool {
+        match *self {}
+    }
+
+    pub(crate) fn refresh(&mut self) {
+        match *self {}
+    }
+}
+
+#[cfg(all(test, feature = "watch"))]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+
+    #[test]
+    fn test_changed() {
+        let path = env::temp_dir().join(format!("heh-test-watch-{}", process::id()));
+        fs::write(&path, b"abc").unwrap();
+        let mut watch = FileWatch::new(&path).unwrap();
+        assert!(!watch.changed());
+
+        // The size changes even if the modification time is too coarse to tell.
+        fs::write(&path, b"abcd").unwrap();
+        assert!(watch.changed());
+        assert!(!watch.changed());
+
+        fs::write(&path, b"abcde").unwrap();
+        watch.refresh();
+        assert!(!watch.changed());
+
+        fs::remove_file(&path).unwrap();
+        assert!(watch.changed());
+        assert!(!watch.changed());
+    }
+}
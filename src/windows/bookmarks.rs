@@ -0,0 +1,172 @@
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{app::AppData, bookmarks::Bookmarks, label::LabelHandler, screen::ScreenHandler};
+
+use super::{adjust_offset, KeyHandler, PopupOutput, Window};
+
+/// The number of bookmarks that are shown in the list at once.
+const VISIBLE_BOOKMARKS: usize = 10;
+
+/// A window that accepts the label of a new bookmark at the cursor.
+///
+/// This can be opened by pressing `CNTRLb` on an offset that isn't bookmarked yet. The label may be
+/// left empty.
+#[derive(PartialEq, Eq)]
+pub(crate) struct BookmarkLabel {
+    pub(crate) input: String,
+}
+
+impl KeyHandler for BookmarkLabel {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::BookmarkLabel
+    }
+    fn char(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler, c: char) {
+        self.input.push(c);
+    }
+    fn get_user_input(&self) -> PopupOutput<'_> {
+        PopupOutput::Str(&self.input)
+    }
+    fn backspace(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.input.pop();
+    }
+    fn enter(&mut self, app: &mut AppData, _: &mut ScreenHandler, labels: &mut LabelHandler) {
+        let label = self.input.trim();
+        let label = if label.is_empty() { None } else { Some(label.to_owned()) };
+        app.bookmarks.toggle(app.offset, label);
+        labels.notification = format!("Bookmarked {:#X}!", app.offset);
+    }
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((50, 3))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        Paragraph::new(Span::styled(&self.input, Style::default().fg(Color::White))).block(
+            Block::default()
+                .title("Bookmark Label:")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl BookmarkLabel {
+    pub(crate) fn new() -> Self {
+        Self { input: String::new() }
+    }
+}
+
+/// A window that lists all bookmarks and moves the cursor to the selected one.
+///
+/// This can be opened by pressing `CNTRLl`. The bookmarks are copied when it opens, as they can't
+/// change while it is focused.
+#[derive(PartialEq, Eq)]
+pub(crate) struct BookmarkList {
+    entries: Vec<(usize, Option<String>)>,
+    selected: usize,
+}
+
+impl KeyHandler for BookmarkList {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::BookmarkList
+    }
+    fn up(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+    fn down(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1));
+    }
+    fn home(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = 0;
+    }
+    fn end(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = self.entries.len().saturating_sub(1);
+    }
+    fn page_up(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = self.selected.saturating_sub(VISIBLE_BOOKMARKS);
+    }
+    fn page_down(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected =
+            (self.selected + VISIBLE_BOOKMARKS).min(self.entries.len().saturating_sub(1));
+    }
+    fn enter(&mut self, app: &mut AppData, display: &mut ScreenHandler, labels: &mut LabelHandler) {
+        if let Some(&(offset, _)) = self.entries.get(self.selected) {
+            jump_to(app, display, labels, offset);
+        }
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((50, self.entries.len().min(VISIBLE_BOOKMARKS) as u16 + 2))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        // Scroll just enough to keep the selected bookmark visible.
+        let first = (self.selected + 1).saturating_sub(VISIBLE_BOOKMARKS);
+        let lines = self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(VISIBLE_BOOKMARKS)
+            .map(|(i, (offset, label))| {
+                let mut span =
+                    Span::from(format!("{:#010X}  {}", offset, label.as_deref().unwrap_or("")));
+                span.style = if i == self.selected {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                Spans::from(span)
+            })
+            .collect::<Vec<Spans>>();
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("Bookmarks:")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl BookmarkList {
+    /// Lists `bookmarks`, selecting the first one at or after `offset`.
+    pub(crate) fn new(bookmarks: &Bookmarks, offset: usize) -> Self {
+        let entries = bookmarks
+            .iter()
+            .map(|(offset, label)| (offset, label.map(str::to_owned)))
+            .collect::<Vec<_>>();
+        let selected = entries
+            .iter()
+            .position(|&(marked, _)| marked >= offset)
+            .unwrap_or_else(|| entries.len().saturating_sub(1));
+        Self { entries, selected }
+    }
+}
+
+/// Moves the cursor to the next (or previous) bookmark, wrapping around at the ends of the file.
+pub(crate) fn jump_to_bookmark(
+    app: &mut AppData,
+    display: &mut ScreenHandler,
+    labels: &mut LabelHandler,
+    forward: bool,
+) {
+    let found =
+        if forward { app.bookmarks.next(app.offset) } else { app.bookmarks.prev(app.offset) };
+    if let Some(found) = found {
+        jump_to(app, display, labels, found);
+    } else {
+        labels.notification = String::from("No bookmarks!");
+    }
+}
+
+fn jump_to(
+    app: &mut AppData,
+    display: &mut ScreenHandler,
+    labels: &mut LabelHandler,
+    offset: usize,
+) {
+    app.offset = offset;
+    labels.update_all(&app.contents[app.offset..], app.endian);
+    adjust_offset(app, display, labels);
+}
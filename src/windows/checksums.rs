@@ -0,0 +1,78 @@
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    app::AppData,
+    hash::{hashes, HashSummary},
+    label::LabelHandler,
+    screen::ScreenHandler,
+};
+
+use super::{copy_to_clipboard, KeyHandler, Window};
+
+/// A window that shows the checksums of the selection, or of the whole file if nothing is selected,
+/// and copies the chosen one to the clipboard.
+///
+/// This can be opened by pressing `ALTh`. The checksums are computed when it opens.
+#[derive(PartialEq, Eq)]
+pub(crate) struct Checksums {
+    summary: HashSummary,
+    selection: bool,
+    selected: usize,
+}
+
+impl KeyHandler for Checksums {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::Checksums
+    }
+    fn up(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+    fn down(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = (self.selected + 1).min(self.summary.entries().len() - 1);
+    }
+    fn enter(&mut self, app: &mut AppData, _: &mut ScreenHandler, labels: &mut LabelHandler) {
+        let (name, value) = self.summary.entries()[self.selected];
+        copy_to_clipboard(app, labels, value.to_owned(), name);
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((76, self.summary.entries().len() as u16 + 2))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        let lines = self
+            .summary
+            .entries()
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, value))| {
+                let mut span = Span::from(format!("{name:<9}{value}"));
+                span.style = if i == self.selected {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                Spans::from(span)
+            })
+            .collect::<Vec<Spans>>();
+        let title = if self.selection { "Checksums of Selection:" } else { "Checksums of File:" };
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl Checksums {
+    /// Computes the checksums of the selection, or of the whole file if nothing is selected.
+    pub(crate) fn new(app: &AppData) -> Self {
+        let selection = app.last_drag.is_some();
+        let bytes = if selection { &app.contents[app.selection().range()] } else { &app.contents };
+        Self { summary: hashes(bytes), selection, selected: 0 }
+    }
+}
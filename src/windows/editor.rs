@@ -28,13 +28,13 @@ impl KeyHandler for Editor {
         match self {
             Self::Ascii => {
                 app.offset = app.offset.saturating_sub(1);
-                labels.update_all(&app.contents[app.offset..]);
+                labels.update_all(&app.contents[app.offset..], app.endian);
                 adjust_offset(app, display, labels);
             }
             Self::Hex => {
                 if app.nibble == Nibble::Beginning {
                     app.offset = app.offset.saturating_sub(1);
-                    labels.update_all(&app.contents[app.offset..]);
+                    labels.update_all(&app.contents[app.offset..], app.endian);
                     adjust_offset(app, display, labels);
                 }
                 app.nibble.toggle();
@@ -47,13 +47,13 @@ impl KeyHandler for Editor {
         match self {
             Self::Ascii => {
                 app.offset = cmp::min(app.offset.saturating_add(1), app.contents.len() - 1);
-                labels.update_all(&app.contents[app.offset..]);
+                labels.update_all(&app.contents[app.offset..], app.endian);
                 adjust_offset(app, display, labels);
             }
             Self::Hex => {
                 if app.nibble == Nibble::End {
                     app.offset = cmp::min(app.offset.saturating_add(1), app.contents.len() - 1);
-                    labels.update_all(&app.contents[app.offset..]);
+                    labels.update_all(&app.contents[app.offset..], app.endian);
                     adjust_offset(app, display, labels);
                 }
                 app.nibble.toggle();
@@ -65,7 +65,7 @@ impl KeyHandler for Editor {
         app.drag_nibble = None;
         if let Some(new_offset) = app.offset.checked_sub(display.comp_layouts.bytes_per_line) {
             app.offset = new_offset;
-            labels.update_all(&app.contents[app.offset..]);
+            labels.update_all(&app.contents[app.offset..], app.endian);
             adjust_offset(app, display, labels);
         }
     }
@@ -75,7 +75,7 @@ impl KeyHandler for Editor {
         if let Some(new_offset) = app.offset.checked_add(display.comp_layouts.bytes_per_line) {
             if new_offset < app.contents.len() {
                 app.offset = new_offset;
-                labels.update_all(&app.contents[app.offset..]);
+                labels.update_all(&app.contents[app.offset..], app.endian);
                 adjust_offset(app, display, labels);
             }
         }
@@ -85,7 +85,7 @@ impl KeyHandler for Editor {
         app.drag_nibble = None;
         let bytes_per_line = display.comp_layouts.bytes_per_line;
         app.offset = app.offset / bytes_per_line * bytes_per_line;
-        labels.update_all(&app.contents[app.offset..]);
+        labels.update_all(&app.contents[app.offset..], app.endian);
         adjust_offset(app, display, labels);
 
         if self.is_focusing(Window::Hex) {
@@ -100,7 +100,7 @@ impl KeyHandler for Editor {
             app.offset + (bytes_per_line - 1 - app.offset % bytes_per_line),
             app.contents.len() - 1,
         );
-        labels.update_all(&app.contents[app.offset..]);
+        labels.update_all(&app.contents[app.offset..], app.endian);
         adjust_offset(app, display, labels);
 
         if self.is_focusing(Window::Hex) {
@@ -118,7 +118,7 @@ impl KeyHandler for Editor {
         app.offset = app.offset.saturating_sub(
             display.comp_layouts.bytes_per_line * display.comp_layouts.lines_per_screen,
         );
-        labels.update_all(&app.contents[app.offset..]);
+        labels.update_all(&app.contents[app.offset..], app.endian);
         adjust_offset(app, display, labels);
     }
     fn page_down(
@@ -135,7 +135,7 @@ impl KeyHandler for Editor {
             ),
             app.contents.len() - 1,
         );
-        labels.update_all(&app.contents[app.offset..]);
+        labels.update_all(&app.contents[app.offset..], app.endian);
         adjust_offset(app, display, labels);
     }
     fn backspace(
@@ -144,13 +144,13 @@ impl KeyHandler for Editor {
         display: &mut ScreenHandler,
         labels: &mut LabelHandler,
     ) {
-        if app.offset > 0 {
-            app.actions.push(Action::Backspace(
-                app.offset.saturating_sub(1),
-                app.contents.remove(app.offset - 1),
-            ));
-            app.offset = app.offset.saturating_sub(1);
-            labels.update_all(&app.contents[app.offset..]);
+        if app.offset > 0 && app.contents.len() > 1 {
+            let offset = app.offset - 1;
+            if let Err(error) = app.edit(Action::Backspace(offset, app.contents[offset])) {
+                labels.notification = error.to_string();
+                return;
+            }
+            labels.update_all(&app.contents[app.offset..], app.endian);
             adjust_offset(app, display, labels);
         }
     }
@@ -161,8 +161,12 @@ impl KeyHandler for Editor {
         labels: &mut LabelHandler,
     ) {
         if app.contents.len() > 1 {
-            app.actions.push(Action::Delete(app.offset, app.contents.remove(app.offset)));
-            labels.update_all(&app.contents[app.offset..]);
+            let offset = app.offset;
+            if let Err(error) = app.edit(Action::Delete(offset, app.contents[offset])) {
+                labels.notification = error.to_string();
+                return;
+            }
+            labels.update_all(&app.contents[app.offset..], app.endian);
             adjust_offset(app, display, labels);
         }
     }
@@ -177,48 +181,63 @@ impl KeyHandler for Editor {
         app.drag_nibble = None;
         match *self {
             Self::Ascii => {
-                app.actions.push(Action::CharacterInput(
-                    app.offset,
-                    app.contents[app.offset],
-                    None,
-                ));
-                app.contents[app.offset] = c as u8;
-                app.offset = cmp::min(app.offset.saturating_add(1), app.contents.len() - 1);
-                labels.update_all(&app.contents[app.offset..]);
+                let bytes = match app.encoding.encode_char(c) {
+                    Some(bytes) if app.insert_mode => bytes,
+                    Some(bytes) if app.offset + bytes.len() <= app.contents.len() => bytes,
+                    Some(_) => {
+                        labels.notification = format!("Not enough room for {c}");
+                        return;
+                    }
+                    None => {
+                        labels.notification = format!("Invalid character: {c}");
+                        return;
+                    }
+                };
+                let len = bytes.len();
+                let start = app.offset;
+                for (i, byte) in bytes.into_iter().enumerate() {
+                    let offset = start + i;
+                    let action = if app.insert_mode {
+                        Action::Insert(offset, byte)
+                    } else {
+                        Action::CharacterInput(offset, app.contents[offset], byte, None)
+                    };
+                    if let Err(error) = app.edit(action) {
+                        labels.notification = error.to_string();
+                        return;
+                    }
+                }
+                app.offset = cmp::min(start + len, app.contents.len() - 1);
+                labels.update_all(&app.contents[app.offset..], app.endian);
                 adjust_offset(app, display, labels);
             }
             Self::Hex => {
-                app.actions.push(Action::CharacterInput(
-                    app.offset,
-                    app.contents[app.offset],
-                    Some(app.nibble),
-                ));
-                if c.is_ascii_hexdigit() {
-                    // This can probably be optimized...
-                    match app.nibble {
-                        Nibble::Beginning => {
-                            let mut src = c.to_string();
-                            src.push(
-                                format!("{:02X}", app.contents[app.offset]).chars().last().unwrap(),
-                            );
-                            let changed = u8::from_str_radix(src.as_str(), 16).unwrap();
-                            app.contents[app.offset] = changed;
+                if let Some(digit) = c.to_digit(16).and_then(|digit| u8::try_from(digit).ok()) {
+                    if app.insert_mode && app.nibble == Nibble::Beginning {
+                        // The new byte is typed into like any other, so it starts out empty.
+                        let offset = app.offset;
+                        if let Err(error) = app.edit(Action::Insert(offset, 0)) {
+                            labels.notification = error.to_string();
+                            return;
                         }
-                        Nibble::End => {
-                            let mut src = format!("{:02X}", app.contents[app.offset])
-                                .chars()
-                                .take(1)
-                                .collect::<String>();
-                            src.push(c);
-                            let changed = u8::from_str_radix(src.as_str(), 16).unwrap();
-                            app.contents[app.offset] = changed;
+                        app.offset = offset;
+                    }
+                    let old = app.contents[app.offset];
+                    let changed = match app.nibble {
+                        Nibble::Beginning => (digit << 4) | (old & 0x0F),
+                        Nibble::End => (old & 0xF0) | digit,
+                    };
+                    let action = Action::CharacterInput(app.offset, old, changed, Some(app.nibble));
+                    if let Err(error) = app.edit(action) {
+                        labels.notification = error.to_string();
+                        return;
+                    }
 
-                            // Move to the next byte
-                            app.offset =
-                                cmp::min(app.offset.saturating_add(1), app.contents.len() - 1);
-                            labels.update_all(&app.contents[app.offset..]);
-                            adjust_offset(app, display, labels);
-                        }
+                    if app.nibble == Nibble::End {
+                        // Move to the next byte
+                        app.offset = cmp::min(app.offset.saturating_add(1), app.contents.len() - 1);
+                        labels.update_all(&app.contents[app.offset..], app.endian);
+                        adjust_offset(app, display, labels);
                     }
                     app.nibble.toggle();
                 } else {
@@ -230,3 +249,42 @@ impl KeyHandler for Editor {
 
     fn enter(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {}
 }
+
+/// Writes `bytes` at the cursor, inserting them in insert mode and overwriting the bytes under the
+/// cursor otherwise. In overwrite mode, the bytes that don't fit before the end of the file are
+/// left out.
+///
+/// The cursor is moved past the pasted bytes.
+pub(crate) fn paste(
+    app: &mut AppData,
+    display: &mut ScreenHandler,
+    labels: &mut LabelHandler,
+    bytes: &[u8],
+) {
+    app.last_drag = None;
+    app.drag_nibble = None;
+    let start = app.offset;
+    let pasted =
+        if app.insert_mode { bytes } else { &bytes[..bytes.len().min(app.contents.len() - start)] };
+    for (i, &byte) in pasted.iter().enumerate() {
+        let offset = start + i;
+        let action = if app.insert_mode {
+            Action::Insert(offset, byte)
+        } else {
+            Action::CharacterInput(offset, app.contents[offset], byte, None)
+        };
+        if let Err(error) = app.edit(action) {
+            labels.notification = error.to_string();
+            return;
+        }
+    }
+    labels.notification = if pasted.len() < bytes.len() {
+        format!("Pasted {} of {} bytes!", pasted.len(), bytes.len())
+    } else {
+        format!("Pasted {} bytes!", pasted.len())
+    };
+    app.offset = cmp::min(start + pasted.len(), app.contents.len() - 1);
+    app.nibble = Nibble::Beginning;
+    labels.update_all(&app.contents[app.offset..], app.endian);
+    adjust_offset(app, display, labels);
+}
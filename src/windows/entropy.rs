@@ -0,0 +1,102 @@
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    app::AppData,
+    entropy::{byte_histogram, entropy_profile, shannon_entropy, sparkline},
+    label::LabelHandler,
+    screen::ScreenHandler,
+};
+
+use super::{KeyHandler, Window};
+
+/// The number of columns the sparklines take up.
+const SPARKLINE_WIDTH: usize = 64;
+
+/// A window that shows how the entropy of the file changes along its offsets, the entropy of the
+/// viewport, and how often each byte value occurs in the selection (or the viewport if nothing is
+/// selected).
+///
+/// This can be opened by pressing `ALTt`. Everything is computed when it opens.
+#[derive(PartialEq)]
+pub(crate) struct Entropy {
+    /// The entropy of the viewport in bits per byte.
+    viewport: f64,
+    /// The entropy along the file, drawn as bars.
+    profile: String,
+    /// The byte frequencies, drawn as bars of four byte values each.
+    histogram: String,
+    /// Whether the byte frequencies are of the selection rather than the viewport.
+    selection: bool,
+    /// The number of bytes each bar of the profile stands for.
+    bytes_per_bar: usize,
+}
+
+impl KeyHandler for Entropy {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::Entropy
+    }
+    fn enter(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {}
+    #[allow(clippy::cast_possible_truncation)]
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((SPARKLINE_WIDTH as u16 + 2, 7))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        let white = Style::default().fg(Color::White);
+        let histogram_title = if self.selection {
+            "Byte values of the selection (00 to FF):"
+        } else {
+            "Byte values of the viewport (00 to FF):"
+        };
+        let lines = vec![
+            Spans::from(Span::styled(
+                format!("Viewport: {:.2} bits per byte", self.viewport),
+                white,
+            )),
+            Spans::from(Span::styled(
+                format!("Along the file ({:#X} bytes per bar):", self.bytes_per_bar),
+                white,
+            )),
+            Spans::from(Span::styled(self.profile.clone(), white)),
+            Spans::from(Span::styled(histogram_title, white)),
+            Spans::from(Span::styled(self.histogram.clone(), white)),
+        ];
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("Entropy:")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl Entropy {
+    pub(crate) fn new(app: &AppData, display: &ScreenHandler) -> Self {
+        let bytes_per_screen =
+            display.comp_layouts.bytes_per_line * display.comp_layouts.lines_per_screen;
+        let viewport = &app.contents
+            [app.start_address..app.contents.len().min(app.start_address + bytes_per_screen)];
+
+        let profile = entropy_profile(&app.contents, SPARKLINE_WIDTH);
+        let bytes_per_bar = (app.contents.len() + SPARKLINE_WIDTH - 1) / SPARKLINE_WIDTH;
+
+        let selection = app.last_drag.is_some();
+        let counted = if selection { &app.contents[app.selection().range()] } else { viewport };
+        let buckets = byte_histogram(counted)
+            .chunks(256 / SPARKLINE_WIDTH)
+            .map(|bucket| f64::from(bucket.iter().sum::<u32>()))
+            .collect::<Vec<_>>();
+        let most = buckets.iter().copied().fold(0.0, f64::max);
+
+        Self {
+            viewport: shannon_entropy(viewport),
+            profile: sparkline(&profile, 8.0),
+            histogram: sparkline(&buckets, most),
+            selection,
+            bytes_per_bar,
+        }
+    }
+}
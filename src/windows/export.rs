@@ -0,0 +1,71 @@
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    app::AppData,
+    export::{format_export, ExportStyle},
+    label::LabelHandler,
+    screen::ScreenHandler,
+};
+
+use super::{copy_to_clipboard, KeyHandler, Window};
+
+/// A window that lets the user pick a language to copy the selection to the clipboard as a literal
+/// of.
+///
+/// This can be opened by pressing `CNTRLx`.
+#[derive(PartialEq, Eq)]
+pub(crate) struct Export {
+    selected: usize,
+}
+
+impl KeyHandler for Export {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::Export
+    }
+    fn up(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+    fn down(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = (self.selected + 1).min(ExportStyle::ALL.len() - 1);
+    }
+    fn enter(&mut self, app: &mut AppData, _: &mut ScreenHandler, labels: &mut LabelHandler) {
+        let style = ExportStyle::ALL[self.selected];
+        let export = format_export(&app.contents[app.selection().range()], style);
+        copy_to_clipboard(app, labels, export, &style.to_string());
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((50, ExportStyle::ALL.len() as u16 + 2))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        let lines = ExportStyle::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, style)| {
+                let mut span = Span::from(style.to_string());
+                span.style = if i == self.selected {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                Spans::from(span)
+            })
+            .collect::<Vec<Spans>>();
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("Export Selection as:")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl Export {
+    pub(crate) fn new() -> Self {
+        Self { selected: 0 }
+    }
+}
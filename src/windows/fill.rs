@@ -0,0 +1,88 @@
+use tui::{
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    app::{Action, AppData},
+    fill::fill_range,
+    label::LabelHandler,
+    paste::{parse_paste, ParseError, PasteMode},
+    screen::ScreenHandler,
+};
+
+use super::{KeyHandler, PopupOutput, Window};
+
+/// A window that accepts a pattern to overwrite the selection (or the byte under the cursor) with.
+///
+/// This can be opened by pressing `ALTf`. The pattern is either hex bytes like "0xFF" or "de ad",
+/// or text in the active encoding, as when pasting.
+#[derive(PartialEq, Eq)]
+pub(crate) struct Fill {
+    pub(crate) input: String,
+}
+
+impl KeyHandler for Fill {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::Fill
+    }
+    fn char(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler, c: char) {
+        self.input.push(c);
+    }
+    fn get_user_input(&self) -> PopupOutput<'_> {
+        PopupOutput::Str(&self.input)
+    }
+    fn backspace(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.input.pop();
+    }
+    fn enter(&mut self, app: &mut AppData, _: &mut ScreenHandler, labels: &mut LabelHandler) {
+        let pattern = match parse_paste(&self.input, PasteMode::Detect(app.encoding)) {
+            Ok(pattern) => pattern,
+            Err(ParseError::Empty) => {
+                labels.notification = String::from("Nothing to fill with!");
+                return;
+            }
+            Err(error) => {
+                labels.notification = error.to_string();
+                return;
+            }
+        };
+
+        let range = app.selection().range();
+        let mut filled = app.contents[range.clone()].to_vec();
+        let len = filled.len();
+        if let Err(error) = fill_range(&mut filled, 0..len, &pattern) {
+            labels.notification = error;
+            return;
+        }
+        let actions = range
+            .zip(filled)
+            .map(|(offset, new)| Action::CharacterInput(offset, app.contents[offset], new, None))
+            .collect();
+        match app.edit_group(actions) {
+            Ok(()) => {
+                labels.notification = format!("Filled {len} bytes!");
+                labels.update_all(&app.contents[app.offset..], app.endian);
+            }
+            Err(error) => labels.notification = error.to_string(),
+        }
+    }
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((50, 3))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        Paragraph::new(Span::styled(&self.input, Style::default().fg(Color::White))).block(
+            Block::default()
+                .title("Fill with:")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl Fill {
+    pub(crate) fn new() -> Self {
+        Self { input: String::new() }
+    }
+}
@@ -1,3 +1,8 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    num::IntErrorKind,
+};
+
 use tui::{
     style::{Color, Style},
     text::Span,
@@ -10,9 +15,9 @@ use super::{adjust_offset, KeyHandler, PopupOutput, Window};
 
 /// A window that can accept input and attempt to move the cursor to the inputted byte.
 ///
-/// This can be opened by pressing `CNTRLj`.
+/// This can be opened by pressing `CNTRLj`, or `g` and `:` in the hex editor.
 ///
-/// The input is either parsed as hexadecimal if it is preceded with "0x", or decimal if not.
+/// The input is parsed by [`parse_offset`].
 #[derive(PartialEq, Eq)]
 pub(crate) struct JumpToByte {
     pub(crate) input: String,
@@ -25,34 +30,26 @@ impl KeyHandler for JumpToByte {
     fn char(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler, c: char) {
         self.input.push(c);
     }
-    fn get_user_input(&self) -> PopupOutput {
+    fn get_user_input(&self) -> PopupOutput<'_> {
         PopupOutput::Str(&self.input)
     }
     fn backspace(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
         self.input.pop();
     }
     fn enter(&mut self, app: &mut AppData, display: &mut ScreenHandler, labels: &mut LabelHandler) {
-        let new_offset = if self.input.starts_with("0x") {
-            usize::from_str_radix(&self.input[2..], 16)
-        } else {
-            self.input.parse()
-        };
-        if let Ok(new_offset) = new_offset {
-            if new_offset >= app.contents.len() {
-                labels.notification = String::from("Invalid range!");
-            } else {
+        match parse_offset(&self.input, app.offset, app.contents.len()) {
+            Ok(new_offset) => {
                 app.offset = new_offset;
-                labels.update_all(&app.contents[app.offset..]);
+                labels.update_all(&app.contents[app.offset..], app.endian);
                 adjust_offset(app, display, labels);
             }
-        } else {
-            labels.notification = format!("Error: {:?}", new_offset.unwrap_err());
+            Err(error) => labels.notification = error.to_string(),
         }
     }
     fn dimensions(&self) -> Option<(u16, u16)> {
         Some((50, 3))
     }
-    fn widget(&self) -> Paragraph {
+    fn widget(&self) -> Paragraph<'_> {
         Paragraph::new(Span::styled(&self.input, Style::default().fg(Color::White))).block(
             Block::default()
                 .title("Jump to Byte:")
@@ -67,3 +64,98 @@ impl JumpToByte {
         Self { input: String::new() }
     }
 }
+
+/// The reasons an offset entered by the user can't be parsed.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum OffsetError {
+    /// Nothing but an optional sign has been entered.
+    Empty,
+    /// The number is not a valid hexadecimal or decimal number.
+    Malformed(String),
+}
+
+impl Display for OffsetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OffsetError::Empty => write!(f, "No offset given!"),
+            OffsetError::Malformed(input) => write!(f, "Invalid offset: {input}"),
+        }
+    }
+}
+
+/// Parses the offset to move the cursor to, clamped to the `len` bytes of the file.
+///
+/// The number is parsed as hexadecimal if it is preceded with "0x", or decimal if not. A leading
+/// `+` or `-` makes the offset relative to `current`, so that `+0x100` moves 256 bytes forward and
+/// `-16` moves 16 bytes back.
+pub(crate) fn parse_offset(input: &str, current: usize, len: usize) -> Result<usize, OffsetError> {
+    let input = input.trim();
+    let (sign, number) = match input.chars().next() {
+        Some(sign @ ('+' | '-')) => (Some(sign), &input[1..]),
+        _ => (None, input),
+    };
+    if number.is_empty() {
+        return Err(OffsetError::Empty);
+    }
+
+    let parsed = if let Some(hex) = number.strip_prefix("0x") {
+        usize::from_str_radix(hex, 16)
+    } else {
+        number.parse()
+    };
+    let value = match parsed {
+        Ok(value) => value,
+        // Numbers that don't even fit are out of range either way, so they are clamped as well.
+        Err(error) if error.kind() == &IntErrorKind::PosOverflow => usize::MAX,
+        Err(_) => return Err(OffsetError::Malformed(input.to_string())),
+    };
+
+    let offset = match sign {
+        Some('+') => current.saturating_add(value),
+        Some(_) => current.saturating_sub(value),
+        None => value,
+    };
+    Ok(offset.min(len.saturating_sub(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("0x1F40", 0, 0x10000), Ok(0x1F40));
+        assert_eq!(parse_offset("8000", 0, 0x10000), Ok(8000));
+        assert_eq!(parse_offset("+0x100", 0x10, 0x10000), Ok(0x110));
+        assert_eq!(parse_offset("+16", 0x10, 0x10000), Ok(0x20));
+        assert_eq!(parse_offset("-16", 0x20, 0x10000), Ok(0x10));
+        assert_eq!(parse_offset("-0x10", 0x20, 0x10000), Ok(0x10));
+        assert_eq!(parse_offset(" 42 ", 0, 0x10000), Ok(42));
+    }
+
+    #[test]
+    fn test_parse_offset_clamped() {
+        assert_eq!(parse_offset("0x10000", 0, 0x100), Ok(0xFF));
+        assert_eq!(parse_offset("+1", 0xFF, 0x100), Ok(0xFF));
+        assert_eq!(parse_offset("-0x100", 0x10, 0x100), Ok(0));
+        assert_eq!(parse_offset("99999999999999999999", 0, 0x100), Ok(0xFF));
+        assert_eq!(parse_offset("-99999999999999999999", 0x10, 0x100), Ok(0));
+        assert_eq!(parse_offset(&format!("+{}", usize::MAX), 1, 0x100), Ok(0xFF));
+    }
+
+    #[test]
+    fn test_parse_offset_malformed() {
+        assert_eq!(parse_offset("", 0, 0x100), Err(OffsetError::Empty));
+        assert_eq!(parse_offset("-", 0, 0x100), Err(OffsetError::Empty));
+        assert_eq!(parse_offset("0x", 0, 0x100), Err(OffsetError::Malformed(String::from("0x"))));
+        assert_eq!(
+            parse_offset("12ab", 0, 0x100),
+            Err(OffsetError::Malformed(String::from("12ab")))
+        );
+        assert_eq!(parse_offset("+-1", 0, 0x100), Err(OffsetError::Malformed(String::from("+-1"))));
+        assert_eq!(
+            parse_offset("0xZZ", 0, 0x100),
+            Err(OffsetError::Malformed(String::from("0xZZ")))
+        );
+    }
+}
@@ -1,8 +1,18 @@
 //! The components that implement [`KeyHandler`], which allow them to uniquely react to user input.
 //! Example of a component include the Hex/ASCII editors and the Unsaved Changes warning.
 
+pub(crate) mod bookmarks;
+pub(crate) mod checksums;
 pub(crate) mod editor;
+pub(crate) mod entropy;
+pub(crate) mod export;
+pub(crate) mod fill;
 pub(crate) mod jump_to_byte;
+pub(crate) mod open_file;
+pub(crate) mod reload;
+pub(crate) mod save_as;
+pub(crate) mod search;
+pub(crate) mod strings;
 pub(crate) mod unsaved_changes;
 
 use tui::widgets::Paragraph;
@@ -16,7 +26,19 @@ use crate::{app::AppData, label::LabelHandler, screen::ScreenHandler};
 pub(crate) enum Window {
     Ascii,
     Hex,
+    BookmarkLabel,
+    BookmarkList,
+    Checksums,
+    CloseTab,
+    Entropy,
+    Export,
+    Fill,
     JumpToByte,
+    OpenFile,
+    Reload,
+    SaveAs,
+    Search,
+    Strings,
     UnsavedChanges,
     Label(usize),
     Unhandled,
@@ -54,7 +76,7 @@ pub(crate) trait KeyHandler {
     fn char(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler, _: char) {}
 
     /// Returns user input. Is currently used to get information from popups.
-    fn get_user_input(&self) -> PopupOutput {
+    fn get_user_input(&self) -> PopupOutput<'_> {
         PopupOutput::NoOutput
     }
 
@@ -64,7 +86,7 @@ pub(crate) trait KeyHandler {
     }
 
     /// Returns the contents to display on the screen
-    fn widget(&self) -> Paragraph {
+    fn widget(&self) -> Paragraph<'_> {
         Paragraph::new("")
     }
 }
@@ -93,3 +115,17 @@ pub(crate) fn adjust_offset(
 
     labels.offset = format!("{:#X}", app.offset);
 }
+
+/// Puts `text` on the clipboard, notifying the user that `what` was copied.
+pub(crate) fn copy_to_clipboard(
+    app: &mut AppData,
+    labels: &mut LabelHandler,
+    text: String,
+    what: &str,
+) {
+    labels.notification = match app.clipboard.as_mut().map(|clipboard| clipboard.set_text(text)) {
+        Some(Ok(())) => format!("{what} copied!"),
+        Some(Err(_)) => String::from("Can't copy to clipboard!"),
+        None => String::from("Can't find clipboard!"),
+    };
+}
@@ -0,0 +1,50 @@
+use tui::{
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{app::AppData, label::LabelHandler, screen::ScreenHandler};
+
+use super::{KeyHandler, PopupOutput, Window};
+
+/// A window that accepts the path of a file to open in a new tab.
+///
+/// This can be opened by pressing `CNTRLo`. As opening the file changes the tabs, it is done by
+/// the input handling once enter is pressed, rather than by the window itself.
+#[derive(PartialEq, Eq)]
+pub(crate) struct OpenFile {
+    pub(crate) input: String,
+}
+
+impl KeyHandler for OpenFile {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::OpenFile
+    }
+    fn char(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler, c: char) {
+        self.input.push(c);
+    }
+    fn get_user_input(&self) -> PopupOutput<'_> {
+        PopupOutput::Str(&self.input)
+    }
+    fn backspace(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.input.pop();
+    }
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((50, 3))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        Paragraph::new(Span::styled(&self.input, Style::default().fg(Color::White))).block(
+            Block::default()
+                .title("Open File:")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl OpenFile {
+    pub(crate) fn new() -> Self {
+        Self { input: String::new() }
+    }
+}
@@ -0,0 +1,56 @@
+use tui::{
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use super::{KeyHandler, Window};
+
+/// A window that offers to reload the file after it was changed on disk by another process.
+///
+/// This opens by itself while an editor is focused. Pressing `R` reloads the file, which is handled
+/// by the [`Application`](crate::app::Application) as it replaces the contents of the active tab.
+#[derive(PartialEq, Eq)]
+pub(crate) struct Reload {
+    /// Whether reloading discards edits that weren't saved.
+    unsaved_changes: bool,
+}
+
+impl KeyHandler for Reload {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::Reload
+    }
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((50, 5))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        let message = vec![
+            Spans::from(Span::styled(
+                "Press R to reload, or Esc to keep editing.",
+                Style::default().fg(Color::White),
+            )),
+            Spans::from(Span::from("")),
+            Spans::from(Span::styled(
+                if self.unsaved_changes { "Your unsaved changes will be lost!" } else { "" },
+                Style::default().fg(Color::Red),
+            )),
+        ];
+        Paragraph::new(message).alignment(Alignment::Center).block(
+            Block::default()
+                .title(Span::styled(
+                    "File Changed on Disk.",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ))
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl Reload {
+    pub(crate) fn new(unsaved_changes: bool) -> Self {
+        Self { unsaved_changes }
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use tui::{
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{app::AppData, label::LabelHandler, screen::ScreenHandler};
+
+use super::{KeyHandler, PopupOutput, Window};
+
+/// A window that accepts the path to save the file as, which is edited from then on.
+///
+/// This can be opened by pressing `CNTRLa`. A file that already exists at the path is replaced.
+#[derive(PartialEq, Eq)]
+pub(crate) struct SaveAs {
+    pub(crate) input: String,
+}
+
+impl KeyHandler for SaveAs {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::SaveAs
+    }
+    fn char(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler, c: char) {
+        self.input.push(c);
+    }
+    fn get_user_input(&self) -> PopupOutput<'_> {
+        PopupOutput::Str(&self.input)
+    }
+    fn backspace(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.input.pop();
+    }
+    fn enter(&mut self, app: &mut AppData, _: &mut ScreenHandler, labels: &mut LabelHandler) {
+        if self.input.is_empty() {
+            labels.notification = String::from("Nowhere to save to!");
+            return;
+        }
+        labels.notification = match app.save_as(Path::new(&self.input)) {
+            Ok(()) => format!("Saved as {}!", app.name),
+            Err(error) => format!("Can't save: {error}"),
+        };
+    }
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((50, 3))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        Paragraph::new(Span::styled(&self.input, Style::default().fg(Color::White))).block(
+            Block::default()
+                .title("Save As:")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl SaveAs {
+    pub(crate) fn new() -> Self {
+        Self { input: String::new() }
+    }
+}
@@ -0,0 +1,127 @@
+use tui::{
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    app::AppData,
+    label::LabelHandler,
+    screen::ScreenHandler,
+    search::{self, Query},
+};
+
+use super::{adjust_offset, KeyHandler, PopupOutput, Window};
+
+/// A window that can accept input and move the cursor to the next match of it.
+///
+/// This can be opened by pressing `CNTRLf`. Afterwards, `CNTRLn` and `CNTRLp` jump to the next and
+/// previous match.
+///
+/// The input is searched for as hexadecimal bytes if it is preceded with "0x", or as text in the
+/// active encoding if not.
+#[derive(PartialEq, Eq)]
+pub(crate) struct Search {
+    pub(crate) input: String,
+}
+
+impl KeyHandler for Search {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::Search
+    }
+    fn char(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler, c: char) {
+        self.input.push(c);
+    }
+    fn get_user_input(&self) -> PopupOutput<'_> {
+        PopupOutput::Str(&self.input)
+    }
+    fn backspace(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.input.pop();
+    }
+    fn enter(&mut self, app: &mut AppData, display: &mut ScreenHandler, labels: &mut LabelHandler) {
+        match Query::parse(&self.input) {
+            Ok(query) => {
+                app.search = Some(query);
+                // The search starts at the cursor so that a match under it isn't skipped.
+                jump_to_match(app, display, labels, app.offset, true);
+            }
+            Err(error) => labels.notification = error,
+        }
+    }
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        Some((50, 3))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        Paragraph::new(Span::styled(&self.input, Style::default().fg(Color::White))).block(
+            Block::default()
+                .title("Search:")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl Search {
+    pub(crate) fn new() -> Self {
+        Self { input: String::new() }
+    }
+}
+
+/// Moves the cursor to the next (or previous) match of the most recent search, starting at
+/// `start`, and highlights it.
+pub(crate) fn jump_to_match(
+    app: &mut AppData,
+    display: &mut ScreenHandler,
+    labels: &mut LabelHandler,
+    start: usize,
+    forward: bool,
+) {
+    let (found, len) = match &app.search {
+        Some(Query::Bytes(bytes)) if forward => (app.find_bytes(bytes, start), bytes.len()),
+        Some(Query::Bytes(bytes)) => (app.find_bytes_prev(bytes, start), bytes.len()),
+        Some(Query::Text(text)) => {
+            let len = if let Some(pattern) = search::encode_text(text, app.encoding) {
+                pattern.len()
+            } else {
+                labels.notification = format!("Can't be encoded in {}!", app.encoding);
+                return;
+            };
+            if forward {
+                (app.find_text(text, start), len)
+            } else {
+                (app.find_text_prev(text, start), len)
+            }
+        }
+        None => {
+            labels.notification = String::from("Nothing to search for!");
+            return;
+        }
+    };
+    if let Some(found) = found {
+        app.offset = found;
+        app.search_match = Some(found..found + len);
+        labels.update_all(&app.contents[app.offset..], app.endian);
+        adjust_offset(app, display, labels);
+    } else {
+        app.search_match = None;
+        labels.notification = String::from("No matches!");
+    }
+}
+
+/// Moves the cursor to the next (or previous) non-printable character, wrapping around at the ends
+/// of the file.
+pub(crate) fn jump_to_nonprintable(
+    app: &mut AppData,
+    display: &mut ScreenHandler,
+    labels: &mut LabelHandler,
+    forward: bool,
+) {
+    let found = if forward { app.next_nonprintable() } else { app.prev_nonprintable() };
+    if let Some(found) = found {
+        app.offset = found;
+        labels.update_all(&app.contents[app.offset..], app.endian);
+        adjust_offset(app, display, labels);
+    } else {
+        labels.notification = String::from("No non-printable characters!");
+    }
+}
@@ -0,0 +1,122 @@
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    app::AppData,
+    label::LabelHandler,
+    screen::ScreenHandler,
+    strings::{find_strings, DEFAULT_MIN_LEN},
+};
+
+use super::{adjust_offset, KeyHandler, Window};
+
+/// The number of strings that are shown in the list at once.
+const VISIBLE_STRINGS: usize = 10;
+
+/// The width of the list, including its borders.
+const WIDTH: u16 = 60;
+
+/// A window that lists the runs of printable text in the file, like the `strings` utility, and
+/// moves the cursor to the selected one.
+///
+/// This can be opened by pressing `ALTs`. The strings are decoded with the active encoding, and
+/// `+` and `-` change how many characters they need at least.
+#[derive(PartialEq, Eq)]
+pub(crate) struct Strings {
+    entries: Vec<(usize, String)>,
+    min_len: usize,
+    selected: usize,
+}
+
+impl KeyHandler for Strings {
+    fn is_focusing(&self, window_type: Window) -> bool {
+        window_type == Window::Strings
+    }
+    fn char(&mut self, app: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler, c: char) {
+        let min_len = match c {
+            '+' => self.min_len + 1,
+            '-' => self.min_len.saturating_sub(1).max(1),
+            _ => return,
+        };
+        let offset = self.entries.get(self.selected).map_or(app.offset, |&(offset, _)| offset);
+        *self = Self::with_min_len(app, min_len, offset);
+    }
+    fn up(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+    fn down(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1));
+    }
+    fn home(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = 0;
+    }
+    fn end(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = self.entries.len().saturating_sub(1);
+    }
+    fn page_up(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = self.selected.saturating_sub(VISIBLE_STRINGS);
+    }
+    fn page_down(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
+        self.selected = (self.selected + VISIBLE_STRINGS).min(self.entries.len().saturating_sub(1));
+    }
+    fn enter(&mut self, app: &mut AppData, display: &mut ScreenHandler, labels: &mut LabelHandler) {
+        if let Some(&(offset, _)) = self.entries.get(self.selected) {
+            app.offset = offset;
+            labels.update_all(&app.contents[app.offset..], app.endian);
+            adjust_offset(app, display, labels);
+        } else {
+            labels.notification = String::from("No strings!");
+        }
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    fn dimensions(&self) -> Option<(u16, u16)> {
+        // The height stays the same when the minimum length changes the number of strings.
+        Some((WIDTH, VISIBLE_STRINGS as u16 + 2))
+    }
+    fn widget(&self) -> Paragraph<'_> {
+        // Scroll just enough to keep the selected string visible.
+        let first = (self.selected + 1).saturating_sub(VISIBLE_STRINGS);
+        let lines = self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(VISIBLE_STRINGS)
+            .map(|(i, (offset, string))| {
+                let string = string.chars().take(usize::from(WIDTH) - 14).collect::<String>();
+                let mut span = Span::from(format!("{offset:#010X}  {string}"));
+                span.style = if i == self.selected {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                Spans::from(span)
+            })
+            .collect::<Vec<Spans>>();
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(format!("Strings ({}+ characters, change with +/-):", self.min_len))
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Yellow)),
+        )
+    }
+}
+
+impl Strings {
+    /// Lists the strings in the file, selecting the first one at or after the cursor.
+    pub(crate) fn new(app: &AppData) -> Self {
+        Self::with_min_len(app, DEFAULT_MIN_LEN, app.offset)
+    }
+
+    fn with_min_len(app: &AppData, min_len: usize, offset: usize) -> Self {
+        let entries = find_strings(&app.contents, app.encoding, min_len);
+        let selected = entries
+            .iter()
+            .position(|&(start, _)| start >= offset)
+            .unwrap_or_else(|| entries.len().saturating_sub(1));
+        Self { entries, min_len, selected }
+    }
+}
@@ -9,13 +9,17 @@ use crate::{app::AppData, label::LabelHandler, screen::ScreenHandler};
 
 use super::{KeyHandler, PopupOutput, Window};
 
+/// A window that asks whether to quit (or close the active tab) although there are unsaved changes.
 pub(crate) struct UnsavedChanges {
     pub(crate) should_quit: bool,
+
+    /// Whether the active tab is closed rather than the whole of heh.
+    closing_tab: bool,
 }
 
 impl KeyHandler for UnsavedChanges {
     fn is_focusing(&self, window_type: Window) -> bool {
-        window_type == Window::UnsavedChanges
+        window_type == if self.closing_tab { Window::CloseTab } else { Window::UnsavedChanges }
     }
     fn left(&mut self, _: &mut AppData, _: &mut ScreenHandler, _: &mut LabelHandler) {
         if !self.should_quit {
@@ -27,16 +31,20 @@ impl KeyHandler for UnsavedChanges {
             self.should_quit = false;
         }
     }
-    fn get_user_input(&self) -> PopupOutput {
+    fn get_user_input(&self) -> PopupOutput<'_> {
         PopupOutput::Boolean(self.should_quit)
     }
     fn dimensions(&self) -> Option<(u16, u16)> {
         Some((50, 5))
     }
-    fn widget(&self) -> Paragraph {
+    fn widget(&self) -> Paragraph<'_> {
         let message = vec![
             Spans::from(Span::styled(
-                "Are you sure you want to quit?",
+                if self.closing_tab {
+                    "Are you sure you want to close the file?"
+                } else {
+                    "Are you sure you want to quit?"
+                },
                 Style::default().fg(Color::White),
             )),
             Spans::from(Span::from("")),
@@ -74,6 +82,11 @@ impl KeyHandler for UnsavedChanges {
 
 impl UnsavedChanges {
     pub(crate) fn new() -> Self {
-        UnsavedChanges { should_quit: false }
+        UnsavedChanges { should_quit: false, closing_tab: false }
+    }
+
+    /// Asks whether to close the active tab instead of quitting.
+    pub(crate) fn closing_tab() -> Self {
+        UnsavedChanges { should_quit: false, closing_tab: true }
     }
 }